@@ -0,0 +1,184 @@
+//! Measures every [Operation], particles and clearing the canvas at common resolutions.\
+//! Run with `cargo bench`, add `--features parallel` to measure the parallel renderer.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use qilin::particles::{Emission, Emitter, EmitterShape, ParticleSystem};
+use qilin::render::canvas::Canvas;
+use qilin::render::color::Color;
+use qilin::render::fill::LinearGradient;
+use qilin::render::lighting::{Light, Lighting};
+use qilin::render::sketch::Operation;
+use qilin::simplified::vec2;
+use std::sync::Arc;
+use std::time::Duration;
+
+const RESOLUTIONS: [(u32, u32); 3] = [(640, 480), (1280, 720), (1920, 1080)];
+
+/// Build one operation of every kind, covering about a quarter of the canvas.
+fn operations(width: u32, height: u32) -> Vec<(&'static str, Operation)> {
+    let (w, h) = (width / 2, height / 2);
+    let pos = vec2(width / 4, height / 4);
+
+    vec![
+        (
+            "line",
+            Operation::Line {
+                from: vec2(0, 0),
+                to: vec2(width - 1, height - 1),
+                color: Color::RED,
+            },
+        ),
+        (
+            "circle",
+            Operation::Circle {
+                pos: vec2(width / 2, height / 2),
+                radius: h / 2,
+                fill: Color::GREEN.into(),
+            },
+        ),
+        (
+            "rect",
+            Operation::Rect {
+                pos,
+                width: w,
+                height: h,
+                fill: Color::BLUE.into(),
+            },
+        ),
+        (
+            "rect_translucent",
+            Operation::Rect {
+                pos,
+                width: w,
+                height: h,
+                fill: Color::BLUE.with_alpha(128).into(),
+            },
+        ),
+        (
+            "rect_gradient",
+            Operation::Rect {
+                pos,
+                width: w,
+                height: h,
+                fill: LinearGradient::new(pos, vec2(pos.x, pos.y + h))
+                    .with_stop(0.0, Color::YELLOW)
+                    .with_stop(1.0, Color::RED)
+                    .into(),
+            },
+        ),
+        (
+            "image",
+            Operation::Image {
+                pos,
+                width: w,
+                height: h,
+                data: Arc::new(vec![Color::ORANGE; (w * h) as usize]),
+            },
+        ),
+        (
+            "oval",
+            Operation::Oval {
+                pos,
+                width: w,
+                height: h,
+                fill: Color::CYAN.into(),
+            },
+        ),
+        (
+            "polygon",
+            Operation::Polygon {
+                points: vec![pos, vec2(pos.x + w, pos.y + h / 2), vec2(pos.x, pos.y + h)],
+                fill: Color::MAGENTA.into(),
+            },
+        ),
+    ]
+}
+
+fn bench_clear(c: &mut Criterion) {
+    let mut group = c.benchmark_group("clear");
+
+    for (width, height) in RESOLUTIONS {
+        let mut canvas = Canvas::new(width as usize, height as usize);
+
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{width}x{height}")),
+            |b| b.iter(|| canvas.clear(&Color::GRAY)),
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_operations(c: &mut Criterion) {
+    for (width, height) in RESOLUTIONS {
+        let mut group = c.benchmark_group(format!("operations/{width}x{height}"));
+        let mut canvas = Canvas::new(width as usize, height as usize);
+
+        for (name, op) in operations(width, height) {
+            group.bench_function(name, |b| b.iter(|| canvas.drawable(&op)));
+        }
+
+        group.finish();
+    }
+}
+
+fn bench_particles(c: &mut Criterion) {
+    let mut group = c.benchmark_group("particles");
+    let step = Duration::from_secs_f32(1.0 / 60.0);
+
+    let mut system = ParticleSystem::new()
+        .with_gravity(vec2(0.0, 50.0))
+        .with_emitter(
+            Emitter::new(vec2(640.0, 360.0), Emission::Burst(5000))
+                .with_shape(EmitterShape::Area {
+                    width: 1280.0,
+                    height: 720.0,
+                })
+                .with_lifetime(10.0..10.0),
+        );
+    system.update(step);
+
+    // clone the system for every update, so the particles don't fall off the canvas or die
+    group.bench_function("update/5000", |b| {
+        b.iter_batched_ref(
+            || system.clone(),
+            |system| system.update(step),
+            BatchSize::SmallInput,
+        )
+    });
+
+    let mut canvas = Canvas::new(1280, 720);
+    group.bench_function("draw/5000", |b| b.iter(|| canvas.drawable(&system)));
+
+    group.finish();
+}
+
+fn bench_lighting(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lighting");
+
+    // checkered walls of 32x32 tiles with a light in every other free tile
+    let walls: Vec<bool> = (0..40 * 23).map(|i| (i % 40 + i / 40) % 4 == 0).collect();
+    let mut lighting =
+        Lighting::new(Color::from_rgb(20, 20, 30)).with_tiles(&walls, 40, 32.0, 32.0);
+    for i in (0..40 * 23)
+        .filter(|i| (i % 40 + i / 40) % 4 == 2)
+        .step_by(8)
+    {
+        let pos = vec2((i % 40) as f32 * 32.0 + 16.0, (i / 40) as f32 * 32.0 + 16.0);
+        lighting.add_light(Light::new(pos, 96.0, Color::ORANGE));
+    }
+
+    let mut canvas = Canvas::new(1280, 720);
+    group.bench_function("draw", |b| b.iter(|| canvas.drawable(&lighting)));
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_clear,
+    bench_operations,
+    bench_particles,
+    bench_lighting
+);
+criterion_main!(benches);
@@ -0,0 +1,50 @@
+//! Measures the per-frame cost of drawing text with a warm and a cold glyph cache.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use qilin::render::canvas::Canvas;
+use qilin::render::color::Color;
+use qilin::simplified::vec2;
+use qilin::text::{Font, TextSketch};
+
+const TEXT: &str = "The quick brown fox jumps over the lazy dog. 0123456789";
+
+fn bench_text(c: &mut Criterion) {
+    let mut group = c.benchmark_group("text");
+    let font = Font::from_bytes(include_bytes!("../examples/assets/Roboto-Medium.ttf")).unwrap();
+    let mut canvas = Canvas::new(1280, 720);
+
+    group.bench_function("parse_font", |b| {
+        b.iter(|| {
+            TextSketch::new(
+                vec2(10, 10),
+                include_bytes!("../examples/assets/Roboto-Medium.ttf"),
+            )
+        })
+    });
+
+    for saa in [false, true] {
+        let name = if saa { "subpixel" } else { "grayscale" };
+        let sketch = || {
+            TextSketch::with_font(vec2(10, 10), &font)
+                .with_ssa(saa)
+                .with_color(Color::WHITE)
+                .with_text(TEXT, 24.0)
+        };
+
+        group.bench_function(format!("draw_cached/{name}"), |b| {
+            b.iter(|| canvas.drawable(&sketch()))
+        });
+
+        group.bench_function(format!("draw_uncached/{name}"), |b| {
+            b.iter(|| {
+                font.clear_cache();
+                canvas.drawable(&sketch())
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_text);
+criterion_main!(benches);
@@ -97,6 +97,7 @@ fn main() {
                 resize: true,                              // make window resizeable
                 ..Default::default()
             },
+            ..Default::default()
         })
         .play()
         .expect("Failed to play game");
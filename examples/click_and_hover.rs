@@ -5,7 +5,7 @@ use qilin::game::context::GameContext;
 use qilin::game::game::Game;
 use qilin::render::canvas::Canvas;
 use qilin::render::color::Color;
-use qilin::render::sketch::Sketch;
+use qilin::render::sketch::{OpHandle, Sketch};
 use qilin::scene::Scene;
 use qilin::simplified::vec2;
 use qilin::types::{Button, GameConfig, FPS30};
@@ -13,7 +13,9 @@ use qilin::ScaleMode;
 use qilin::WindowOptions;
 
 struct InputScene {
-    color: Color,
+    sketch: Sketch,
+    circle: OpHandle,
+    rect: OpHandle,
 }
 
 impl Scene for InputScene {
@@ -22,8 +24,21 @@ impl Scene for InputScene {
     where
         Self: Sized,
     {
+        // build the sketch only once and keep handles to modify the shapes later
+        let mut sketch = Sketch::new();
+        let circle = sketch
+            .circle(vec2(100, 100), 50, Color::WHITE)
+            .last_handle()
+            .unwrap();
+        let rect = sketch
+            .rect(vec2(200, 200), 200, 10, Color::WHITE)
+            .last_handle()
+            .unwrap();
+
         Self {
-            color: Color::WHITE,
+            sketch,
+            circle,
+            rect,
         }
     }
 
@@ -32,20 +47,32 @@ impl Scene for InputScene {
 
     // gets called when window requests draw updates
     fn update(&mut self, canvas: &mut Canvas, ctx: &mut GameContext) {
-        let mut sketch = Sketch::new();
-        sketch
-            .circle(vec2(100, 100), 50, self.color)
-            .rect(vec2(200, 200), 200, 10, self.color);
+        let hovering = ctx.hovering_on_sketch(&self.sketch);
 
-        if ctx.clicked_on_sketch(&sketch, Button::Mouse(MouseButton::Left)) {
-            self.color = Color::BLUE;
-        } else if ctx.hovering_on_sketch(&sketch) {
-            self.color = Color::RED;
-        } else {
-            self.color = Color::WHITE;
+        // clicks only fire once per press
+        if ctx.clicked_on_sketch(&self.sketch, Button::Mouse(MouseButton::Left)) {
+            println!("Clicked!");
         }
 
-        canvas.draw(&sketch);
+        // wheel and motion are measured since the last frame
+        let (_, scroll) = ctx.get_scroll_wheel();
+        if scroll != 0.0 {
+            println!("Scrolled by {scroll}, moved by {:?}", ctx.get_mouse_delta());
+        }
+
+        let color = if hovering && ctx.is_mouse_down(MouseButton::Left) {
+            Color::BLUE
+        } else if hovering {
+            Color::RED
+        } else {
+            Color::WHITE
+        };
+
+        // recolor the existing shapes instead of creating a new sketch
+        self.sketch.set_fill(self.circle, color);
+        self.sketch.set_fill(self.rect, color);
+
+        canvas.draw(&self.sketch);
     }
 
     fn fixed_update(&mut self, _canvas: &mut Canvas, _ctx: &mut GameContext) {
@@ -71,6 +98,7 @@ fn main() {
                 resize: true,                              // make window resizeable
                 ..Default::default()
             },
+            ..Default::default()
         })
         .play()
         .expect("Failed to play game");
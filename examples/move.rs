@@ -2,12 +2,13 @@
 
 use qilin::game::context::GameContext;
 use qilin::game::game::Game;
+use qilin::game::input::InputMap;
 use qilin::render::canvas::Canvas;
 use qilin::render::color::Color;
 use qilin::render::sketch::Sketch;
 use qilin::scene::Scene;
 use qilin::simplified::vec2;
-use qilin::types::{GameConfig, TimeStamp, FPS60};
+use qilin::types::{Button, GameConfig, TimeStamp, FPS60};
 use qilin::Key;
 use qilin::ScaleMode;
 use qilin::Vector2;
@@ -22,6 +23,7 @@ const SPEED: u32 = 2;
 
 struct BounceScene {
     pos: Vector2<u32>,
+    rebinding: bool,
 }
 
 impl Scene for BounceScene {
@@ -32,14 +34,29 @@ impl Scene for BounceScene {
     {
         Self {
             pos: vec2(100, 100),
+            rebinding: false,
         }
     }
 
     // gets called when game enters current scene
-    fn enter(&mut self) { println!("What do you call a fake noodle?") }
+    fn enter(&mut self) {
+        println!("What do you call a fake noodle?");
+        println!("Press 'r' and then any button to rebind the 'slow' action.");
+    }
 
     // gets called when window requests draw updates
-    fn update(&mut self, canvas: &mut Canvas, _ctx: &mut GameContext) {
+    fn update(&mut self, canvas: &mut Canvas, ctx: &mut GameContext) {
+        // wait for the next pressed button and bind the action to it
+        if self.rebinding {
+            if let Some(button) = ctx.get_pressed_button() {
+                ctx.input_map().rebind("slow", &[button]);
+                println!("'slow' is now bound to {}", button.name());
+                self.rebinding = false;
+            }
+        } else if ctx.is_key_just_pressed(Key::R) {
+            self.rebinding = true;
+        }
+
         // draw walls in red
         canvas.draw(
             Sketch::new()
@@ -54,24 +71,14 @@ impl Scene for BounceScene {
     }
 
     fn fixed_update(&mut self, _canvas: &mut Canvas, ctx: &mut GameContext) {
-        // move player if key pressed and not hitting wall
-        {
-            if ctx.is_key_down(Key::W) && self.pos.y > MIN_Y {
-                self.pos.y -= SPEED;
-            }
+        // move player along the input axes without hitting a wall
+        let speed = if ctx.is_action_down("slow") { SPEED / 2 } else { SPEED };
+        let speed = speed as f32;
+        let x = self.pos.x as f32 + ctx.get_axis("horizontal") * speed;
+        let y = self.pos.y as f32 + ctx.get_axis("vertical") * speed;
 
-            if ctx.is_key_down(Key::S) && self.pos.y < MAX_Y {
-                self.pos.y += SPEED;
-            }
-
-            if ctx.is_key_down(Key::D) && self.pos.x < MAX_X {
-                self.pos.x += SPEED;
-            }
-
-            if ctx.is_key_down(Key::A) && self.pos.x > MIN_X {
-                self.pos.x -= SPEED;
-            }
-        }
+        self.pos.x = x.clamp(MIN_X as f32, MAX_X as f32) as u32;
+        self.pos.y = y.clamp(MIN_Y as f32, MAX_Y as f32) as u32;
     }
 
     // gets called when game exits current scene
@@ -91,6 +98,19 @@ fn main() {
                 resize: true,                              // make window resizeable
                 ..Default::default()
             },
+            input_map: InputMap::new() // bind actions and axes to keys or mouse buttons
+                .with_action("slow", &[Button::Keyboard(Key::LeftShift)])
+                .with_axis(
+                    "horizontal",
+                    &[Button::Keyboard(Key::A), Button::Keyboard(Key::Left)],
+                    &[Button::Keyboard(Key::D), Button::Keyboard(Key::Right)],
+                )
+                .with_axis(
+                    "vertical",
+                    &[Button::Keyboard(Key::W), Button::Keyboard(Key::Up)],
+                    &[Button::Keyboard(Key::S), Button::Keyboard(Key::Down)],
+                ),
+            ..Default::default()
         })
         .play()
         .expect("Failed to play game");
@@ -12,7 +12,7 @@ use qilin::render::color::Color;
 use qilin::render::sketch::Sketch;
 use qilin::scene::Scene;
 use qilin::simplified::vec2;
-use qilin::text::TextSketch;
+use qilin::text::{Font, TextSketch};
 use qilin::types::GameConfig;
 use qilin::ScaleMode;
 
@@ -31,6 +31,7 @@ struct PingPongScene {
     window_width: i32,
     window_height: i32,
     score: i32,
+    font: Font,
 }
 
 impl Scene for PingPongScene {
@@ -45,6 +46,8 @@ impl Scene for PingPongScene {
             window_width: 800,
             window_height: 600,
             score: 0,
+            font: Font::from_bytes(include_bytes!("assets/Roboto-Medium.ttf"))
+                .expect("Failed to load font"),
         }
     }
 
@@ -59,7 +62,7 @@ impl Scene for PingPongScene {
     fn update(&mut self, canvas: &mut Canvas, ctx: &mut GameContext) {
         // score
         canvas.drawable(
-            &TextSketch::new(vec2(350, 10), include_bytes!("assets/Roboto-Medium.ttf"))
+            &TextSketch::with_font(vec2(350, 10), &self.font)
                 .with_text(format!("Score: {}", self.score).as_str(), 20.0)
                 .with_color(Color::YELLOW),
         );
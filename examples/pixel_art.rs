@@ -0,0 +1,64 @@
+//! Shows how to draw on a small logical canvas, which gets scaled to the window with black bars.
+
+use qilin::game::context::GameContext;
+use qilin::game::game::Game;
+use qilin::render::canvas::Canvas;
+use qilin::render::color::Color;
+use qilin::render::sketch::Sketch;
+use qilin::render::viewport::{LogicalResolution, Scaling};
+use qilin::scene::Scene;
+use qilin::simplified::vec2;
+use qilin::types::GameConfig;
+use qilin::WindowOptions;
+
+struct PixelScene;
+
+impl Scene for PixelScene {
+    fn new() -> Self
+    where
+        Self: Sized,
+    {
+        Self
+    }
+
+    fn enter(&mut self) {}
+
+    fn update(&mut self, canvas: &mut Canvas, ctx: &mut GameContext) {
+        canvas.clear(&Color::from_hex("#1d2b53").unwrap());
+
+        // the canvas is always 320x180, no matter how large the window is
+        let mut sketch = Sketch::new();
+        sketch
+            .rect(vec2(0, 150), 320, 30, Color::from_hex("#008751").unwrap())
+            .circle(vec2(260, 40), 16, Color::YELLOW);
+
+        // mouse positions are mapped to the logical canvas
+        if let Some((x, y)) = ctx.get_mouse_pos() {
+            sketch.rect(vec2(x as u32, y as u32), 4, 4, Color::RED);
+        }
+
+        canvas.draw(&sketch);
+    }
+
+    fn fixed_update(&mut self, _canvas: &mut Canvas, _ctx: &mut GameContext) {}
+
+    fn exit(&mut self) {}
+}
+
+fn main() {
+    Game::new::<PixelScene>()
+        .with_config(GameConfig {
+            title: "Pixel Art".to_string(),
+            width: 1000, // initial window width
+            height: 600, // initial window height
+            window: WindowOptions {
+                resize: true, // make window resizeable
+                ..Default::default()
+            },
+            // scale a 320x180 canvas by whole numbers only, use Scaling::Fractional to fill the window
+            logical_resolution: Some(LogicalResolution::new(320, 180).with_scaling(Scaling::Integer)),
+            ..Default::default()
+        })
+        .play()
+        .expect("Failed to play game");
+}
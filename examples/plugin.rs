@@ -10,6 +10,8 @@ use std::time::Duration;
 
 use qilin::scene::Scene;
 
+use qilin::plugin::recorder::RecorderPlugin;
+use qilin::plugin::screenshot::ScreenshotPlugin;
 use qilin::plugin::QPlugin;
 use qilin::render::color::Color;
 use qilin::render::sketch::Sketch;
@@ -63,6 +65,16 @@ impl QPlugin for MyPlugin {
 fn main() {
     Game::new::<PluginScene>()
         .with_plugin(MyPlugin {})
+        // press F12 to save a screenshot
+        .with_plugin(ScreenshotPlugin::new().with_on_capture(|result| match result {
+            Ok(path) => println!("Saved screenshot to {}", path.display()),
+            Err(err) => eprintln!("Failed to save screenshot: {err}"),
+        }))
+        // press F10 to start and stop recording
+        .with_plugin(RecorderPlugin::new().with_on_finish(|result| match result {
+            Ok(path) => println!("Saved recording to {}", path.display()),
+            Err(err) => eprintln!("Failed to save recording: {err}"),
+        }))
         .with_config(GameConfig {
             title: "Player Preferences".to_string(), // set window title
             update_rate_limit: FPS30,       // limit update rate to 30 fps, default is 60 fps
@@ -74,6 +86,7 @@ fn main() {
                 resize: true,                              // make window resizeable
                 ..Default::default()
             },
+            ..Default::default()
         })
         .play()
         .expect("Failed to play game");
@@ -4,6 +4,7 @@ use qilin::game::context::GameContext;
 use qilin::game::game::Game;
 use qilin::render::canvas::Canvas;
 use qilin::render::color::Color;
+use qilin::render::fill::LinearGradient;
 use qilin::render::sketch::Sketch;
 use qilin::scene::Scene;
 use qilin::simplified::vec2;
@@ -43,6 +44,25 @@ impl Scene for ShapeScene {
                 .rect(vec2(300, 300), 100, 100, Color::BLUE)
                 .oval(vec2(400, 200), 120, 60, Color::CYAN),
         );
+
+        // draw shapes filled with a gradient instead of a single color
+        canvas.draw(
+            Sketch::new()
+                // draw rectangle with a gradient from yellow (top) to red (bottom)
+                .rect(
+                    vec2(550, 350),
+                    150,
+                    100,
+                    LinearGradient::new(vec2(550, 350), vec2(550, 450))
+                        .with_stop(0.0, Color::YELLOW)
+                        .with_stop(1.0, Color::RED),
+                )
+                // draw triangle with color magenta
+                .polygon(
+                    vec![vec2(100, 450), vec2(200, 550), vec2(50, 550)],
+                    Color::MAGENTA,
+                ),
+        );
     }
 
     fn fixed_update(&mut self, _canvas: &mut Canvas, _ctx: &mut GameContext) {
@@ -68,6 +88,7 @@ fn main() {
                 resize: true,                              // make window resizeable
                 ..Default::default()
             },
+            ..Default::default()
         })
         .play()
         .expect("Failed to play game");
@@ -7,12 +7,17 @@ use qilin::render::color::Color;
 
 use qilin::scene::Scene;
 use qilin::simplified::vec2;
-use qilin::text::TextSketch;
+use qilin::text::effects::TextAnimation;
+use qilin::text::{Font, HorizontalAlign, TextSketch, VerticalAlign};
 use qilin::types::{GameConfig, FPS30};
 use qilin::ScaleMode;
 use qilin::WindowOptions;
+use std::time::Instant;
 
-struct TextScene;
+struct TextScene {
+    font: Font,
+    start: Instant,
+}
 
 impl Scene for TextScene {
     // create new empty scene
@@ -20,7 +25,12 @@ impl Scene for TextScene {
     where
         Self: Sized,
     {
-        Self
+        // load the font once, so glyphs get cached between frames
+        Self {
+            font: Font::from_bytes(include_bytes!("assets/Roboto-Medium.ttf"))
+                .expect("Failed to load font"),
+            start: Instant::now(),
+        }
     }
 
     // gets called when game enters current scene
@@ -29,18 +39,45 @@ impl Scene for TextScene {
     // gets called when window requests draw updates
     fn update(&mut self, canvas: &mut Canvas, _ctx: &mut GameContext) {
         canvas.drawable(
-            &TextSketch::new(vec2(10, 10), include_bytes!("assets/Roboto-Medium.ttf"))
+            &TextSketch::with_font(vec2(10, 10), &self.font)
                 .with_color(Color::RED)
                 // .with_ssa(true) // we don't need this, since ssa is enabled by default
                 .with_text("This is with Subpixel Anti-Aliased", 30.0),
         );
 
         canvas.drawable(
-            &TextSketch::new(vec2(10, 100), include_bytes!("assets/Roboto-Medium.ttf"))
+            &TextSketch::with_font(vec2(10, 100), &self.font)
                 .with_color(Color::RED)
                 .with_ssa(false)
                 .with_text("This is without Subpixel Anti-Aliased", 30.0),
         );
+
+        // wrap text into a box and center it
+        canvas.drawable(
+            &TextSketch::with_font(vec2(10, 200), &self.font)
+                .with_color(Color::BLUE)
+                .with_text("This text gets wrapped and centered inside of a box", 30.0)
+                .with_bounds(400.0, 200.0)
+                .with_align(HorizontalAlign::Center, VerticalAlign::Middle),
+        );
+
+        // use markup to change the color and size of parts of the text
+        canvas.drawable(
+            &TextSketch::with_font(vec2(10, 450), &self.font)
+                .with_color(Color::WHITE)
+                .with_markup("Watch out, [color=red][size=40]Danger[/size][/color] ahead!", 30.0),
+        );
+
+        // reveal outlined text like a typewriter
+        canvas.drawable(
+            &TextSketch::with_font(vec2(10, 520), &self.font)
+                .with_color(Color::WHITE)
+                .with_outline(Color::BLACK, 2)
+                .with_shadow(Color::GRAY, vec2(3, 3))
+                .with_text("Once upon a time...", 30.0)
+                .with_animation(TextAnimation::Typewriter { speed: 10.0 })
+                .with_time(self.start.elapsed()),
+        );
     }
 
     fn fixed_update(&mut self, _canvas: &mut Canvas, _ctx: &mut GameContext) {
@@ -66,6 +103,7 @@ fn main() {
                 resize: true,                              // make window resizeable
                 ..Default::default()
             },
+            ..Default::default()
         })
         .play()
         .expect("Failed to play game");
@@ -1,18 +1,78 @@
+use crate::game::input::{InputMap, InputState};
 use crate::render::color::Color;
+use crate::render::post::PostProcess;
 use crate::render::sketch::{Operation, Sketch};
+use crate::render::viewport::Viewport;
 use crate::types::Button;
 use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window};
+use std::time::Duration;
 
 /// Game controller to execute game logic and get input.
 #[derive(Debug)]
 pub struct GameContext<'a> {
     window: &'a mut Window,
+    pub(crate) state: EngineState,
+    viewport: Option<Viewport>,
+}
+
+/// Engine state, which the game loop moves into the [GameContext] of every frame.
+#[derive(Debug, Default)]
+pub(crate) struct EngineState {
+    pub(crate) post_process: PostProcess,
+    pub(crate) input_map: InputMap,
+    pub(crate) input: InputState,
 }
 
 impl<'a> GameContext<'a> {
-    /// Create context from a [Window].
+    /// Create context from a [Window] without post-processing, actions or input,
+    /// e.g. to test scenes and plugins outside of the game loop.
+    #[inline]
+    pub fn new(window: &'a mut Window) -> GameContext<'a> {
+        Self {
+            window,
+            state: EngineState::default(),
+            viewport: None,
+        }
+    }
+
+    /// Use `post_process` as [PostProcess] chain.
     #[inline]
-    pub fn new(window: &'a mut Window) -> GameContext<'a> { Self { window } }
+    pub fn with_post_process(mut self, post_process: PostProcess) -> Self {
+        self.state.post_process = post_process;
+        self
+    }
+
+    /// Use `input_map` as [InputMap].
+    #[inline]
+    pub fn with_input_map(mut self, input_map: InputMap) -> Self {
+        self.state.input_map = input_map;
+        self
+    }
+
+    /// Use `input` as [InputState] of the current frame.
+    #[inline]
+    pub fn with_input(mut self, input: InputState) -> Self {
+        self.state.input = input;
+        self
+    }
+
+    /// Use the engine `state` of the game loop.
+    #[inline]
+    pub(crate) fn with_state(mut self, state: EngineState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Use `viewport` to map mouse positions from the window to a logical canvas.
+    #[inline]
+    pub fn with_viewport(mut self, viewport: Option<Viewport>) -> Self {
+        self.viewport = viewport;
+        self
+    }
+
+    /// Get the [Viewport] of the logical canvas, if [crate::types::GameConfig::logical_resolution] is set.
+    #[inline]
+    pub fn viewport(&self) -> Option<Viewport> { self.viewport }
 
     /// Toggle if the window is topmost.
     #[inline]
@@ -20,7 +80,19 @@ impl<'a> GameContext<'a> {
 
     /// Get the minifb [Window].
     #[inline]
-    pub fn window(&mut self) -> &Window { &self.window }
+    pub fn window(&mut self) -> &Window { self.window }
+
+    /// Get the [PostProcess] chain to add, remove or toggle screen effects.
+    #[inline]
+    pub fn post_process(&mut self) -> &mut PostProcess { &mut self.state.post_process }
+
+    /// Get the [InputMap] to query or rebind actions and axes.
+    #[inline]
+    pub fn input_map(&mut self) -> &mut InputMap { &mut self.state.input_map }
+
+    /// Get the [InputState] of the current frame.
+    #[inline]
+    pub fn input(&self) -> &InputState { &self.state.input }
 
     /// Set the background color of the window.\
     /// Use [crate::render::canvas::Canvas::clear] to clear canvas color.
@@ -70,6 +142,53 @@ impl<'a> GameContext<'a> {
     #[inline]
     pub fn is_mouse_down(&self, button: MouseButton) -> bool { self.window.get_mouse_down(button) }
 
+    /// Check if a mouse button went down since the last frame.
+    #[inline]
+    pub fn is_mouse_just_pressed(&self, button: MouseButton) -> bool {
+        self.state.input.is_just_pressed(Button::Mouse(button))
+    }
+
+    /// Check if a mouse button went up since the last frame.
+    #[inline]
+    pub fn is_mouse_just_released(&self, button: MouseButton) -> bool {
+        self.state.input.is_just_released(Button::Mouse(button))
+    }
+
+    /// Check if a key went down since the last frame.\
+    /// Unlike [GameContext::is_key_pressed], this is the same during the whole frame,
+    /// also in every [crate::scene::Scene::fixed_update] of it.
+    #[inline]
+    pub fn is_key_just_pressed(&self, key: Key) -> bool {
+        self.state.input.is_just_pressed(Button::Keyboard(key))
+    }
+
+    /// Check if a key went up since the last frame.
+    #[inline]
+    pub fn is_key_just_released(&self, key: Key) -> bool {
+        self.state.input.is_just_released(Button::Keyboard(key))
+    }
+
+    /// Check if a key or mouse button went down since the last frame.
+    #[inline]
+    pub fn is_button_just_pressed(&self, button: Button) -> bool {
+        self.state.input.is_just_pressed(button)
+    }
+
+    /// Check if a key or mouse button went up since the last frame.
+    #[inline]
+    pub fn is_button_just_released(&self, button: Button) -> bool {
+        self.state.input.is_just_released(button)
+    }
+
+    /// Get how long a key or mouse button has been down.
+    ///
+    /// Returns [None] if the button is up.
+    #[inline]
+    pub fn get_held_duration(&self, button: Button) -> Option<Duration> {
+        self.state.input.held_duration(button)
+    }
+
+    /// Check if a key or mouse button is down.
     pub fn is_button_down(&self, button: Button) -> bool {
         match button {
             Button::Keyboard(key) => self.is_key_down(key),
@@ -77,11 +196,103 @@ impl<'a> GameContext<'a> {
         }
     }
 
-    /// Get mouse position.
+    /// Check if any button bound to the action `name` of the [InputMap] is down.
+    #[inline]
+    pub fn is_action_down(&self, name: &str) -> bool {
+        self.state
+            .input_map
+            .is_down(name, |button| self.is_button_down(button))
+    }
+
+    /// Check if any button bound to the action `name` went down since the last frame.
+    #[inline]
+    pub fn is_action_just_pressed(&self, name: &str) -> bool {
+        self.state
+            .input_map
+            .is_down(name, |button| self.state.input.is_just_pressed(button))
+    }
+
+    /// Check if any button bound to the action `name` went up since the last frame.
+    #[inline]
+    pub fn is_action_just_released(&self, name: &str) -> bool {
+        self.state
+            .input_map
+            .is_down(name, |button| self.state.input.is_just_released(button))
+    }
+
+    /// Get the value of the axis `name` of the [InputMap]: `-1.0`, `0.0` or `1.0`.\
+    /// Returns `0.0` if both or no directions are held or the axis doesn't exist.
+    #[inline]
+    pub fn get_axis(&self, name: &str) -> f32 {
+        self.state
+            .input_map
+            .value(name, |button| self.is_button_down(button))
+    }
+
+    /// Get a key or mouse button, which went down since the last frame,
+    /// e.g. to wait for the new button when rebinding an action.
+    #[inline]
+    pub fn get_pressed_button(&self) -> Option<Button> {
+        self.state.input.just_pressed().first().copied()
+    }
+
+    /// Get mouse position.\
+    /// With a logical resolution, the position is mapped to the canvas and clamped to its size.
     ///
-    /// Returns `None` if the mouse is outside of the window.
+    /// Returns [None] if the mouse is outside of the window.
     pub fn get_mouse_pos(&self) -> Option<(f32, f32)> {
-        self.window.get_mouse_pos(MouseMode::Clamp)
+        let (x, y) = self.window.get_mouse_pos(MouseMode::Clamp)?;
+
+        match self.viewport {
+            Some(viewport) => {
+                let (x, y) = self.to_canvas(x, y);
+                let max_x = viewport.logical.width.saturating_sub(1) as f32;
+                let max_y = viewport.logical.height.saturating_sub(1) as f32;
+                Some((x.clamp(0.0, max_x), y.clamp(0.0, max_y)))
+            }
+            None => Some((x, y)),
+        }
+    }
+
+    /// Get mouse position on the canvas, without clamping it.\
+    /// With a logical resolution, the position is mapped to the canvas,
+    /// otherwise it is scaled by the window [minifb::Scale].
+    /// Positions outside of the window or on the bars are outside of the canvas.
+    ///
+    /// Returns [None] if the window doesn't know the position.
+    #[inline]
+    pub fn get_mouse_pos_unclamped(&self) -> Option<(f32, f32)> {
+        let (x, y) = self.state.input.mouse_pos()?;
+        Some(self.to_canvas(x, y))
+    }
+
+    /// Get mouse position in window pixels, without clamping or mapping it to the canvas.
+    ///
+    /// Returns [None] if the window doesn't know the position.
+    #[inline]
+    pub fn get_window_mouse_pos(&self) -> Option<(f32, f32)> { self.state.input.window_mouse_pos() }
+
+    /// Get how far the mouse moved on the canvas since the last frame.
+    #[inline]
+    pub fn get_mouse_delta(&self) -> (f32, f32) {
+        let (dx, dy) = self.state.input.mouse_delta();
+        match self.viewport {
+            Some(viewport) => (dx / viewport.scale, dy / viewport.scale),
+            None => (dx, dy),
+        }
+    }
+
+    /// Get how far the scroll wheel moved since the last frame, horizontally and vertically.
+    #[inline]
+    pub fn get_scroll_wheel(&self) -> (f32, f32) { self.state.input.scroll_wheel() }
+
+    /// Map a mouse position, scaled by the window [minifb::Scale], to the canvas.
+    #[inline]
+    fn to_canvas(&self, x: f32, y: f32) -> (f32, f32) {
+        match self.viewport {
+            Some(viewport) => viewport.to_logical(x, y),
+            None => (x, y),
+        }
     }
 
     /// Check if the mouse is hovering on top of (Drawing)-Operation.
@@ -110,7 +321,7 @@ impl<'a> GameContext<'a> {
             }
             Operation::Circle {
                 radius,
-                color: _,
+                fill: _,
                 pos,
             } => {
                 // Calculate the distance between the mouse position and the circle center
@@ -122,7 +333,7 @@ impl<'a> GameContext<'a> {
                 distance_squared <= (radius * radius) as f32
             }
             Operation::Rect {
-                color: _,
+                fill: _,
                 pos,
                 width,
                 height,
@@ -134,10 +345,10 @@ impl<'a> GameContext<'a> {
                     && mouse_y <= (pos.y + height) as f32
             }
             Operation::Image {
-                pos,
-                width,
-                height,
-                data: _,
+                pos, width, height, ..
+            }
+            | Operation::Canvas {
+                pos, width, height, ..
             } => {
                 // Check if the mouse position is within the image boundaries
                 mouse_x >= pos.x as f32
@@ -146,7 +357,7 @@ impl<'a> GameContext<'a> {
                     && mouse_y <= (pos.y + height) as f32
             }
             Operation::Oval {
-                color: _,
+                fill: _,
                 pos,
                 width,
                 height,
@@ -162,37 +373,47 @@ impl<'a> GameContext<'a> {
                 // Check if the distance is less than or equal to the square of the largest radius (half of the width or height)
                 distance_squared <= ((width * width * height * height) / 4) as f32
             }
+            Operation::Polygon { points, fill: _ } => {
+                // Count how many edges a ray from the mouse position to the right crosses
+                let mut inside = false;
+                for (i, a) in points.iter().enumerate() {
+                    let b = &points[(i + 1) % points.len()];
+                    let (ax, ay) = (a.x as f32, a.y as f32);
+                    let (bx, by) = (b.x as f32, b.y as f32);
+
+                    if (ay > mouse_y) != (by > mouse_y)
+                        && mouse_x < ax + (mouse_y - ay) * (bx - ax) / (by - ay)
+                    {
+                        inside = !inside;
+                    }
+                }
+                inside
+            }
         }
     }
 
-    /// Check if the mouse is hovering on a sketch.
+    /// Check if the mouse is hovering on a visible operation of a sketch.
     #[inline]
     pub fn hovering_on_sketch(&self, sketch: &Sketch) -> bool {
-        for op in sketch.get_operations() {
+        for op in sketch.visible_operations() {
             if self.hovering_on(op) {
                 return true;
             }
         }
-        return false;
+        false
     }
 
-    /// Check if the mouse has clicked on an operation
+    /// Check if the mouse has clicked on an operation.\
+    /// Only true in the frame `btn` went down, use [GameContext::is_button_down] to check if it is held.
     #[inline]
     pub fn clicked_on(&self, operation: &Operation, btn: Button) -> bool {
-        if self.hovering_on(operation) && self.is_button_down(btn) {
-            true
-        } else {
-            false
-        }
+        self.is_button_just_pressed(btn) && self.hovering_on(operation)
     }
 
-    /// Check if the mouse has clicked on a sketch
+    /// Check if the mouse has clicked on a sketch.\
+    /// Only true in the frame `btn` went down, use [GameContext::is_button_down] to check if it is held.
     #[inline]
     pub fn clicked_on_sketch(&self, sketch: &Sketch, btn: Button) -> bool {
-        if self.hovering_on_sketch(sketch) && self.is_button_down(btn) {
-            true
-        } else {
-            false
-        }
+        self.is_button_just_pressed(btn) && self.hovering_on_sketch(sketch)
     }
 }
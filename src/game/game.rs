@@ -1,6 +1,9 @@
-use crate::game::context::GameContext;
+use crate::game::context::{EngineState, GameContext};
+use crate::game::input::InputState;
 use crate::plugin::QPlugin;
 use crate::render::canvas::Canvas;
+use crate::render::post::PostProcess;
+use crate::render::viewport::Viewport;
 use crate::scene::Scene;
 use crate::types::GameConfig;
 use minifb::Window;
@@ -73,7 +76,23 @@ impl Game {
 
         window.limit_update_rate(Some(self.config.update_rate_limit));
 
-        let mut canvas = Canvas::new(*width, *height);
+        // with a logical resolution, the canvas keeps its size and gets scaled to the window
+        let logical = self.config.logical_resolution;
+        let (canvas_width, canvas_height) = match logical {
+            Some(logical) => (logical.width, logical.height),
+            None => (*width, *height),
+        };
+        let mut screen: Vec<u32> = Vec::new();
+
+        let mut canvas = Canvas::new(canvas_width, canvas_height);
+        canvas.set_dirty_tracking(self.config.dirty_tracking);
+
+        let mut presenter = Presenter::new(canvas_width, canvas_height);
+        let mut state = EngineState {
+            post_process: std::mem::take(&mut self.config.post_process),
+            input_map: std::mem::take(&mut self.config.input_map),
+            input: InputState::new(),
+        };
 
         let fixed_time_step = self.config.fixed_time_step.0;
 
@@ -86,13 +105,24 @@ impl Game {
         });
 
         while window.is_open() {
-            let ctx = &mut GameContext::new(&mut window);
+            let (window_width, window_height) = window.get_size();
+            let viewport =
+                logical.map(|logical| Viewport::new(window_width, window_height, logical));
+
             let current_time = Instant::now();
+            state.input.capture(&window, current_time);
+
+            let ctx = &mut GameContext::new(&mut window)
+                .with_state(std::mem::take(&mut state))
+                .with_viewport(viewport);
             let delta_time = current_time - last_time;
             last_time = current_time;
             accumulated_time += delta_time;
 
-            canvas.cleanse();
+            // with dirty tracking, the scene only redraws what changed
+            if !canvas.is_dirty_tracking() {
+                canvas.cleanse();
+            }
 
             // call plugins before update
             self.plugins.iter_mut().for_each(|pl| {
@@ -112,7 +142,42 @@ impl Game {
                 accumulated_time -= fixed_time_step;
             }
 
-            window.update_with_buffer(&canvas.clone().buffer().as_slice(), *width, *height)?;
+            let hooks = !self.plugins.is_empty();
+            let frame = presenter.frame(&mut canvas, ctx.post_process(), viewport, hooks);
+
+            // call plugins before presenting
+            self.plugins.iter_mut().for_each(|pl| {
+                pl.pre_present(frame, ctx);
+            });
+
+            state = std::mem::take(&mut ctx.state);
+
+            // skip presenting if nothing got drawn, but still process window events
+            let (frame, dirty, partial) = presenter.finish(&canvas);
+
+            match viewport {
+                Some(viewport) if dirty && window_width > 0 && window_height > 0 => {
+                    // only scale the damaged regions, if the screen still shows the last frame
+                    if partial && screen.len() == window_width * window_height {
+                        viewport.present_rects(
+                            frame,
+                            &mut screen,
+                            window_width,
+                            &frame.dirty_rects(),
+                        );
+                    } else {
+                        screen.resize(window_width * window_height, 0);
+                        viewport.present(frame, &mut screen, window_width);
+                    }
+                    window.update_with_buffer(&screen, window_width, window_height)?;
+                }
+                None if dirty => {
+                    window.update_with_buffer(frame.get_buffer().as_slice(), *width, *height)?;
+                }
+                _ => window.update(),
+            }
+
+            canvas.reset_damage();
         }
 
         // make sure the last scene also calls exit()'s
@@ -123,6 +188,135 @@ impl Game {
             pl.on_exit(&mut canvas);
         });
 
-        return Ok(());
+        Ok(())
+    }
+}
+
+/// Prepares the frame to present and tracks what changed since the last presented frame.
+struct Presenter {
+    /// Post-processed frame, so the canvas keeps the unprocessed frame for dirty tracking.
+    output: Canvas,
+    processing: bool,
+    was_processing: bool,
+    viewport: Option<Viewport>,
+    last_viewport: Option<Viewport>,
+}
+
+impl Presenter {
+    /// Create a new presenter for a canvas with `width` and `height`.
+    #[inline]
+    fn new(width: usize, height: usize) -> Self {
+        let mut output = Canvas::new(width, height);
+        output.set_dirty_tracking(true);
+
+        Self {
+            output,
+            processing: false,
+            was_processing: false,
+            viewport: None,
+            last_viewport: None,
+        }
+    }
+
+    /// Get the frame for the `pre_present` hooks, which is `canvas` or a post-processed copy of it.\
+    /// If `hooks` may draw on the copy, it gets refreshed every frame, so their drawings don't pile up.
+    #[inline]
+    fn frame<'a>(
+        &'a mut self,
+        canvas: &'a mut Canvas,
+        post_process: &PostProcess,
+        viewport: Option<Viewport>,
+        hooks: bool,
+    ) -> &'a mut Canvas {
+        self.processing = post_process.is_active();
+        self.viewport = viewport;
+        if !self.processing {
+            return canvas;
+        }
+
+        // post-processing effects can depend on the whole frame, so they always process all of it
+        self.output.reset_damage();
+        if hooks || canvas.is_dirty() || !self.was_processing || viewport != self.last_viewport {
+            self.output.copy_from(canvas);
+            post_process.apply(&mut self.output);
+        }
+        &mut self.output
+    }
+
+    /// Get the frame to present after the hooks drew on it, whether it changed since the last frame
+    /// and whether presenting its dirty rects is enough.
+    #[inline]
+    fn finish<'a>(&'a mut self, canvas: &'a Canvas) -> (&'a Canvas, bool, bool) {
+        let changed = self.processing != self.was_processing || self.viewport != self.last_viewport;
+        let frame = if self.processing {
+            &self.output
+        } else {
+            canvas
+        };
+        let dirty = changed || frame.is_dirty();
+        let partial = canvas.is_dirty_tracking() && !self.processing && !changed;
+
+        self.was_processing = self.processing;
+        self.last_viewport = self.viewport;
+        (frame, dirty, partial)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::game::Presenter;
+    use crate::render::canvas::Canvas;
+    use crate::render::color::Color;
+    use crate::render::post::{Effect, PostProcess};
+    use crate::render::sketch::Sketch;
+    use crate::simplified::vec2;
+
+    /// Run the presenting part of a frame, where a `pre_present` hook draws `overlay`.\
+    /// Returns the presented pixels or [None] if nothing got presented.
+    fn present(
+        presenter: &mut Presenter,
+        canvas: &mut Canvas,
+        post_process: &PostProcess,
+        overlay: &Sketch,
+    ) -> Option<Vec<u32>> {
+        let frame = presenter.frame(canvas, post_process, None, true);
+        frame.draw(overlay);
+
+        let (frame, dirty, _) = presenter.finish(canvas);
+        let pixels = dirty.then(|| frame.get_buffer().as_slice().to_vec());
+        canvas.reset_damage();
+        pixels
+    }
+
+    fn overlay() -> Sketch {
+        let mut sketch = Sketch::new();
+        sketch.rect(vec2(0, 0), 2, 2, Color::WHITE.with_alpha(128));
+        sketch
+    }
+
+    #[test]
+    fn presents_drawings_of_hooks() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.set_dirty_tracking(true);
+        let mut presenter = Presenter::new(4, 4);
+        let post_process = PostProcess::new();
+
+        assert!(present(&mut presenter, &mut canvas, &post_process, &Sketch::new()).is_some());
+        assert!(present(&mut presenter, &mut canvas, &post_process, &Sketch::new()).is_none());
+        assert!(present(&mut presenter, &mut canvas, &post_process, &overlay()).is_some());
+    }
+
+    #[test]
+    fn refreshes_post_processed_frame_for_hooks() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.set_dirty_tracking(true);
+        let mut presenter = Presenter::new(4, 4);
+        let post_process = PostProcess::new().with(Effect::Custom(|_| {}));
+
+        let first = present(&mut presenter, &mut canvas, &post_process, &overlay()).unwrap();
+        let second = present(&mut presenter, &mut canvas, &post_process, &overlay()).unwrap();
+
+        assert_eq!(Color(first[0]), Color::from_rgb(128, 128, 128));
+        assert_eq!(first, second);
     }
 }
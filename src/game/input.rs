@@ -0,0 +1,469 @@
+#[cfg(feature = "store")]
+use crate::game::store::{PlayerPrefs, Storable};
+use crate::types::Button;
+use minifb::{MouseButton, MouseMode, Window};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Buttons of an axis in an [InputMap].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Axis {
+    /// Buttons, which move the axis towards `-1.0`.
+    pub negative: Vec<Button>,
+    /// Buttons, which move the axis towards `1.0`.
+    pub positive: Vec<Button>,
+}
+
+/// Named actions and axes, bound to one or more [Button]s.\
+/// Query them with [crate::game::context::GameContext::is_action_down] and
+/// [crate::game::context::GameContext::get_axis], so players can rebind them at runtime.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InputMap {
+    actions: HashMap<String, Vec<Button>>,
+    axes: HashMap<String, Axis>,
+}
+
+impl InputMap {
+    /// Create an empty input map.
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+    /// Bind the action `name` to `buttons`.
+    #[inline]
+    pub fn with_action(mut self, name: &str, buttons: &[Button]) -> Self {
+        self.rebind(name, buttons);
+        self
+    }
+
+    /// Bind the axis `name` to `negative` and `positive` buttons.
+    #[inline]
+    pub fn with_axis(mut self, name: &str, negative: &[Button], positive: &[Button]) -> Self {
+        self.rebind_axis(name, negative, positive);
+        self
+    }
+
+    /// Add `button` to the action `name`, creating the action if it doesn't exist.
+    #[inline]
+    pub fn bind(&mut self, name: &str, button: Button) {
+        let buttons = self.actions.entry(name.to_string()).or_default();
+        if !buttons.contains(&button) {
+            buttons.push(button);
+        }
+    }
+
+    /// Remove `button` from the action `name`.
+    ///
+    /// Returns [None] if the action doesn't exist or isn't bound to `button`.
+    #[inline]
+    pub fn unbind(&mut self, name: &str, button: Button) -> Option<()> {
+        let buttons = self.actions.get_mut(name)?;
+        let index = buttons.iter().position(|b| *b == button)?;
+        buttons.remove(index);
+        Some(())
+    }
+
+    /// Replace all buttons of the action `name` with `buttons`.
+    #[inline]
+    pub fn rebind(&mut self, name: &str, buttons: &[Button]) {
+        self.actions.insert(name.to_string(), buttons.to_vec());
+    }
+
+    /// Replace all buttons of the axis `name`.
+    #[inline]
+    pub fn rebind_axis(&mut self, name: &str, negative: &[Button], positive: &[Button]) {
+        self.axes.insert(
+            name.to_string(),
+            Axis {
+                negative: negative.to_vec(),
+                positive: positive.to_vec(),
+            },
+        );
+    }
+
+    /// Remove the action `name`.
+    #[inline]
+    pub fn remove_action(&mut self, name: &str) -> Option<Vec<Button>> { self.actions.remove(name) }
+
+    /// Remove the axis `name`.
+    #[inline]
+    pub fn remove_axis(&mut self, name: &str) -> Option<Axis> { self.axes.remove(name) }
+
+    /// Get the buttons of the action `name`.
+    #[inline]
+    pub fn action(&self, name: &str) -> Option<&[Button]> {
+        self.actions.get(name).map(|buttons| buttons.as_slice())
+    }
+
+    /// Get the buttons of the axis `name`.
+    #[inline]
+    pub fn axis(&self, name: &str) -> Option<&Axis> { self.axes.get(name) }
+
+    /// Iterate over all actions and their buttons.
+    #[inline]
+    pub fn actions(&self) -> impl Iterator<Item = (&str, &[Button])> {
+        self.actions
+            .iter()
+            .map(|(name, buttons)| (name.as_str(), buttons.as_slice()))
+    }
+
+    /// Iterate over all axes and their buttons.
+    #[inline]
+    pub fn axes(&self) -> impl Iterator<Item = (&str, &Axis)> {
+        self.axes.iter().map(|(name, axis)| (name.as_str(), axis))
+    }
+
+    /// Get the names of all actions bound to `button`, e.g. to find conflicts when rebinding.
+    #[inline]
+    pub fn actions_bound_to(&self, button: Button) -> Vec<&str> {
+        self.actions()
+            .filter(|(_, buttons)| buttons.contains(&button))
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Check if any button of the action `name` is down.
+    pub(crate) fn is_down(&self, name: &str, is_down: impl Fn(Button) -> bool) -> bool {
+        self.action(name)
+            .is_some_and(|buttons| buttons.iter().any(|b| is_down(*b)))
+    }
+
+    /// Get the value of the axis `name` between `-1.0` and `1.0`.
+    pub(crate) fn value(&self, name: &str, is_down: impl Fn(Button) -> bool) -> f32 {
+        let Some(axis) = self.axis(name) else {
+            return 0.0;
+        };
+
+        let negative = axis.negative.iter().any(|b| is_down(*b));
+        let positive = axis.positive.iter().any(|b| is_down(*b));
+        positive as i8 as f32 - negative as i8 as f32
+    }
+
+    /// Store all bindings in `prefs` under `"input.action.<name>"` and
+    /// `"input.axis.<name>.negative"` / `"input.axis.<name>.positive"`.\
+    /// Call [PlayerPrefs::save] afterwards to write them to disk.
+    #[cfg(feature = "store")]
+    #[inline]
+    pub fn save(&self, prefs: &mut PlayerPrefs) {
+        let names = |buttons: &[Button]| {
+            Storable::StringVec(
+                buttons
+                    .iter()
+                    .map(|button| button.name().to_string())
+                    .collect(),
+            )
+        };
+
+        for (name, buttons) in self.actions() {
+            prefs.insert(format!("input.action.{name}"), names(buttons));
+        }
+        for (name, axis) in self.axes() {
+            prefs.insert(format!("input.axis.{name}.negative"), names(&axis.negative));
+            prefs.insert(format!("input.axis.{name}.positive"), names(&axis.positive));
+        }
+    }
+
+    /// Replace the bindings of existing actions and axes with the ones stored in `prefs` by [InputMap::save].\
+    /// Bindings missing from `prefs` keep their defaults and unknown button names are skipped.
+    #[cfg(feature = "store")]
+    #[inline]
+    pub fn load(&mut self, prefs: &PlayerPrefs) {
+        let buttons = |key: String| -> Option<Vec<Button>> {
+            let names = prefs.get(key)?.as_string_vec()?;
+            Some(
+                names
+                    .iter()
+                    .filter_map(|name| Button::from_name(name))
+                    .collect(),
+            )
+        };
+
+        for (name, bound) in self.actions.iter_mut() {
+            if let Some(stored) = buttons(format!("input.action.{name}")) {
+                *bound = stored;
+            }
+        }
+        for (name, axis) in self.axes.iter_mut() {
+            if let Some(stored) = buttons(format!("input.axis.{name}.negative")) {
+                axis.negative = stored;
+            }
+            if let Some(stored) = buttons(format!("input.axis.{name}.positive")) {
+                axis.positive = stored;
+            }
+        }
+    }
+}
+
+/// Snapshot of the keys, mouse buttons, mouse position and scroll wheel,
+/// taken once per frame by the game loop.\
+/// Query it with [crate::game::context::GameContext::is_button_just_pressed] and friends.
+#[derive(Clone, Debug)]
+pub struct InputState {
+    down: Vec<(Button, Instant)>,
+    pressed: Vec<Button>,
+    released: Vec<Button>,
+    now: Instant,
+    mouse: Option<(f32, f32)>,
+    previous_mouse: Option<(f32, f32)>,
+    window_mouse: Option<(f32, f32)>,
+    wheel: (f32, f32),
+}
+
+impl Default for InputState {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            down: Vec::new(),
+            pressed: Vec::new(),
+            released: Vec::new(),
+            now: Instant::now(),
+            mouse: None,
+            previous_mouse: None,
+            window_mouse: None,
+            wheel: (0.0, 0.0),
+        }
+    }
+}
+
+impl InputState {
+    /// Create a state without any button down.
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+    /// Take a new snapshot of the keys, mouse buttons and mouse of `window`.
+    pub(crate) fn capture(&mut self, window: &Window, now: Instant) {
+        self.update_mouse(
+            window.get_mouse_pos(MouseMode::Pass),
+            window.get_unscaled_mouse_pos(MouseMode::Pass),
+            window.get_scroll_wheel().unwrap_or((0.0, 0.0)),
+        );
+
+        let keys = window.get_keys().into_iter().map(Button::Keyboard);
+        let mouse = [MouseButton::Left, MouseButton::Middle, MouseButton::Right]
+            .into_iter()
+            .filter(|button| window.get_mouse_down(*button))
+            .map(Button::Mouse);
+
+        self.update(keys.chain(mouse), now);
+    }
+
+    /// Take a new snapshot, where the `down` buttons are down at `now`.
+    pub(crate) fn update(&mut self, down: impl IntoIterator<Item = Button>, now: Instant) {
+        let previous = std::mem::take(&mut self.down);
+        self.pressed.clear();
+        self.released.clear();
+
+        for button in down {
+            if self.down.iter().any(|(b, _)| *b == button) {
+                continue;
+            }
+
+            match previous.iter().find(|(b, _)| *b == button) {
+                Some(held) => self.down.push(*held),
+                None => {
+                    self.pressed.push(button);
+                    self.down.push((button, now));
+                }
+            }
+        }
+
+        for (button, _) in previous {
+            if !self.is_down(button) {
+                self.released.push(button);
+            }
+        }
+        self.now = now;
+    }
+
+    /// Take a new snapshot of the mouse, where `pos` is scaled by the window [minifb::Scale],
+    /// `window_pos` is in window pixels and `wheel` is the scroll since the last frame.
+    pub(crate) fn update_mouse(
+        &mut self,
+        pos: Option<(f32, f32)>,
+        window_pos: Option<(f32, f32)>,
+        wheel: (f32, f32),
+    ) {
+        self.previous_mouse = std::mem::replace(&mut self.mouse, pos);
+        self.window_mouse = window_pos;
+        self.wheel = wheel;
+    }
+
+    /// Check if `button` is down.
+    #[inline]
+    pub fn is_down(&self, button: Button) -> bool { self.down.iter().any(|(b, _)| *b == button) }
+
+    /// Check if `button` went down since the last frame.
+    #[inline]
+    pub fn is_just_pressed(&self, button: Button) -> bool { self.pressed.contains(&button) }
+
+    /// Check if `button` went up since the last frame.
+    #[inline]
+    pub fn is_just_released(&self, button: Button) -> bool { self.released.contains(&button) }
+
+    /// Get how long `button` has been down, measured at the start of the frame.
+    ///
+    /// Returns [None] if the button is up.
+    #[inline]
+    pub fn held_duration(&self, button: Button) -> Option<Duration> {
+        self.down
+            .iter()
+            .find(|(b, _)| *b == button)
+            .map(|(_, since)| self.now - *since)
+    }
+
+    /// Get all buttons, which went down since the last frame.
+    #[inline]
+    pub fn just_pressed(&self) -> &[Button] { &self.pressed }
+
+    /// Get all buttons, which went up since the last frame.
+    #[inline]
+    pub fn just_released(&self) -> &[Button] { &self.released }
+
+    /// Get the mouse position scaled by the window [minifb::Scale], without clamping it to the window.
+    ///
+    /// Returns [None] if the window doesn't know the position.
+    #[inline]
+    pub fn mouse_pos(&self) -> Option<(f32, f32)> { self.mouse }
+
+    /// Get the mouse position in window pixels, without clamping it to the window.
+    ///
+    /// Returns [None] if the window doesn't know the position.
+    #[inline]
+    pub fn window_mouse_pos(&self) -> Option<(f32, f32)> { self.window_mouse }
+
+    /// Get how far the mouse moved since the last frame, scaled like [InputState::mouse_pos].\
+    /// Zero if the position is unknown in this or the last frame.
+    #[inline]
+    pub fn mouse_delta(&self) -> (f32, f32) {
+        match (self.previous_mouse, self.mouse) {
+            (Some((px, py)), Some((x, y))) => (x - px, y - py),
+            _ => (0.0, 0.0),
+        }
+    }
+
+    /// Get how far the scroll wheel moved since the last frame, horizontally and vertically.
+    #[inline]
+    pub fn scroll_wheel(&self) -> (f32, f32) { self.wheel }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::input::{InputMap, InputState};
+    use crate::types::Button;
+    use minifb::{Key, MouseButton};
+    use std::time::{Duration, Instant};
+
+    const JUMP: Button = Button::Keyboard(Key::Space);
+    const FIRE: Button = Button::Mouse(MouseButton::Left);
+
+    fn map() -> InputMap {
+        InputMap::new()
+            .with_action("jump", &[JUMP, Button::Keyboard(Key::W)])
+            .with_axis(
+                "horizontal",
+                &[Button::Keyboard(Key::A)],
+                &[Button::Keyboard(Key::D)],
+            )
+    }
+
+    #[test]
+    fn queries_actions_and_axes() {
+        let map = map();
+
+        assert!(map.is_down("jump", |b| b == JUMP));
+        assert!(!map.is_down("jump", |b| b == FIRE));
+        assert!(!map.is_down("missing", |_| true));
+
+        assert_eq!(
+            map.value("horizontal", |b| b == Button::Keyboard(Key::D)),
+            1.0
+        );
+        assert_eq!(
+            map.value("horizontal", |b| b == Button::Keyboard(Key::A)),
+            -1.0
+        );
+        assert_eq!(map.value("horizontal", |_| true), 0.0);
+        assert_eq!(map.value("missing", |_| true), 0.0);
+    }
+
+    #[test]
+    fn rebinds_actions() {
+        let mut map = map();
+
+        map.bind("jump", FIRE);
+        map.bind("jump", FIRE);
+        assert_eq!(map.action("jump").unwrap().len(), 3);
+        assert_eq!(map.actions_bound_to(FIRE), vec!["jump"]);
+
+        assert!(map.unbind("jump", JUMP).is_some());
+        assert!(map.unbind("jump", JUMP).is_none());
+        assert!(!map.is_down("jump", |b| b == JUMP));
+
+        map.rebind("jump", &[JUMP]);
+        assert_eq!(map.action("jump"), Some([JUMP].as_slice()));
+    }
+
+    #[test]
+    fn names_buttons() {
+        for button in [JUMP, FIRE, Button::Keyboard(Key::NumPad7)] {
+            assert_eq!(Button::from_name(button.name()), Some(button));
+        }
+        assert_eq!(Button::from_name("Unknown"), None);
+        assert_eq!(Button::Keyboard(Key::Escape).name(), "Escape");
+        assert_eq!(Button::Keyboard(Key::Unknown).name(), "Unknown");
+    }
+
+    #[test]
+    fn tracks_presses_between_frames() {
+        let mut state = InputState::new();
+        let start = Instant::now();
+
+        state.update([JUMP, FIRE], start);
+        assert!(state.is_just_pressed(JUMP) && state.is_just_pressed(FIRE));
+        assert_eq!(state.held_duration(FIRE), Some(Duration::ZERO));
+
+        state.update([FIRE], start + Duration::from_millis(100));
+        assert!(!state.is_just_pressed(FIRE) && state.is_down(FIRE));
+        assert!(state.is_just_released(JUMP) && !state.is_down(JUMP));
+        assert_eq!(state.held_duration(FIRE), Some(Duration::from_millis(100)));
+        assert_eq!(state.held_duration(JUMP), None);
+
+        state.update([], start + Duration::from_millis(200));
+        assert_eq!(state.just_released(), [FIRE].as_slice());
+        assert!(state.just_pressed().is_empty());
+    }
+
+    #[test]
+    fn tracks_mouse_between_frames() {
+        let mut state = InputState::new();
+
+        state.update_mouse(Some((10.0, 20.0)), Some((20.0, 40.0)), (0.0, 1.5));
+        assert_eq!(state.mouse_delta(), (0.0, 0.0));
+        assert_eq!(state.scroll_wheel(), (0.0, 1.5));
+
+        state.update_mouse(Some((-5.0, 24.0)), Some((-10.0, 48.0)), (0.0, 0.0));
+        assert_eq!(state.mouse_delta(), (-15.0, 4.0));
+        assert_eq!(state.mouse_pos(), Some((-5.0, 24.0)));
+        assert_eq!(state.window_mouse_pos(), Some((-10.0, 48.0)));
+        assert_eq!(state.scroll_wheel(), (0.0, 0.0));
+
+        state.update_mouse(None, None, (0.0, 0.0));
+        assert_eq!(state.mouse_delta(), (0.0, 0.0));
+    }
+
+    #[cfg(feature = "store")]
+    #[test]
+    fn persists_bindings() {
+        use crate::game::store::PlayerPrefs;
+        use std::path::PathBuf;
+
+        let mut prefs = PlayerPrefs::new(PathBuf::from("unused.json"));
+        let mut custom = map();
+        custom.rebind("jump", &[FIRE]);
+        custom.rebind_axis("horizontal", &[Button::Keyboard(Key::Left)], &[]);
+        custom.save(&mut prefs);
+
+        let mut loaded = map();
+        loaded.load(&prefs);
+        assert_eq!(loaded, custom);
+    }
+}
@@ -1,7 +1,11 @@
 /// Contains [crate::game::context::GameContext] struct
 pub mod context;
 
+/// Contains [input::InputMap] struct for named actions and axes
+pub mod input;
+
 /// Contains [game::Game] struct
+#[allow(clippy::module_inception)]
 pub mod game;
 
 #[cfg(feature = "store")]
@@ -1,7 +1,9 @@
+use crate::render::canvas::Canvas;
 use crate::render::color::Color;
+use crate::render::indexed::{IndexedImage, Palette};
 use crate::types::Image;
 use image::buffer::Pixels;
-use image::{DynamicImage, Rgb};
+use image::{DynamicImage, GenericImageView, Rgb, RgbImage, RgbaImage};
 
 /// Convert a DynamicImage from the `image` crate to a qilin `Image`.
 #[inline]
@@ -17,3 +19,74 @@ pub fn rgb_to_img(rgb: Pixels<Rgb<u8>>) -> Image {
     }
     vec
 }
+
+/// Convert the pixels of a [Canvas] to an `RgbImage` from the `image` crate. Alpha gets dropped.
+#[inline]
+pub fn canvas_to_rgb(canvas: &Canvas) -> RgbImage {
+    RgbImage::from_raw(
+        canvas.width() as u32,
+        canvas.height() as u32,
+        canvas.to_rgb_bytes(),
+    )
+    .expect("canvas buffer matches its size")
+}
+
+/// Convert the pixels of a [Canvas] to an opaque `RgbaImage` from the `image` crate.
+#[inline]
+pub fn canvas_to_rgba(canvas: &Canvas) -> RgbaImage {
+    DynamicImage::from(canvas_to_rgb(canvas)).to_rgba8()
+}
+
+/// Convert a DynamicImage from the `image` crate to an [IndexedImage] at `pos`.\
+/// Every distinct color (including alpha) gets an entry in the palette, in order of appearance.
+/// Returns [None] if the image contains more than 256 colors.
+#[inline]
+pub fn dynamic_to_indexed(dynamic: &DynamicImage, pos: mint::Vector2<u32>) -> Option<IndexedImage> {
+    let (width, height) = dynamic.dimensions();
+    let mut colors: Vec<Color> = Vec::new();
+    let mut indices = Vec::with_capacity(width as usize * height as usize);
+
+    for px in dynamic.to_rgba8().pixels() {
+        let [r, g, b, a] = px.0;
+        let color = Color::from_rgba(r, g, b, a);
+
+        let index = match colors.iter().position(|c| *c == color) {
+            Some(index) => index,
+            None => {
+                colors.push(color);
+                colors.len() - 1
+            }
+        };
+        indices.push(u8::try_from(index).ok()?);
+    }
+
+    IndexedImage::new(pos, width, height, indices, Palette::new(colors))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::image::dynamic_to_indexed;
+    use crate::render::color::Color;
+    use crate::simplified::vec2;
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    #[test]
+    fn extracts_palette_in_order_of_appearance() {
+        let pixels = [[255, 0, 0, 255], [0, 0, 255, 128], [255, 0, 0, 255]];
+        let image = RgbaImage::from_fn(3, 1, |x, _| Rgba(pixels[x as usize]));
+
+        let indexed = dynamic_to_indexed(&DynamicImage::from(image), vec2(1, 2)).unwrap();
+        assert_eq!(indexed.indices(), &[0, 1, 0]);
+        assert_eq!(
+            indexed.palette().colors(),
+            &[Color::RED, Color::from_rgba(0, 0, 255, 128)]
+        );
+        assert_eq!((indexed.width(), indexed.height()), (3, 1));
+    }
+
+    #[test]
+    fn rejects_more_than_256_colors() {
+        let image = RgbaImage::from_fn(257, 1, |x, _| Rgba([x as u8, (x >> 8) as u8, 0, 255]));
+        assert!(dynamic_to_indexed(&DynamicImage::from(image), vec2(0, 0)).is_none());
+    }
+}
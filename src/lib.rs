@@ -2,6 +2,8 @@
 pub mod game;
 /// Contains math functions to manipulate `mint` types, like [Vector2] or [Vector3].
 pub mod math;
+/// Contains the [particles::ParticleSystem] for explosions, dust and other effects.
+pub mod particles;
 /// Contains plugin development features.
 pub mod plugin;
 /// Contains structs for the [game] module to use. Mostly rendering and geometry stuff.
@@ -13,7 +15,7 @@ pub mod simplified;
 /// Contains common types of qilin.
 pub mod types;
 
-/// Contains utils for converting images from the `image` crate to a qilin [types::Image].\
+/// Contains utils for converting images between the `image` crate and qilin.\
 /// Requires `image` feature.
 #[cfg(feature = "image")]
 pub mod image;
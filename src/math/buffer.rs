@@ -18,6 +18,32 @@ impl Buffer {
     pub fn get_height(&self) -> usize { self.height }
 
     pub fn as_slice(&self) -> &[u32] { self.buffer.as_slice() }
+
+    /// Get the pixels of row `y` or [None] if `y` is out of bounds.
+    #[inline]
+    pub(crate) fn row_mut(&mut self, y: usize) -> Option<&mut [u32]> {
+        if y < self.height {
+            Some(&mut self.buffer[y * self.width..(y + 1) * self.width])
+        } else {
+            None
+        }
+    }
+
+    /// Set every pixel to `value`.
+    #[inline]
+    pub(crate) fn fill(&mut self, value: u32) { self.buffer.fill(value); }
+
+    /// Set the pixels of row `y` from `start_x` to `end_x` (exclusive) to `value`.\
+    /// The span gets clipped to the buffer bounds.
+    #[inline]
+    pub(crate) fn fill_span(&mut self, y: usize, start_x: usize, end_x: usize, value: u32) {
+        let end_x = end_x.min(self.width);
+        if let Some(row) = self.row_mut(y) {
+            if start_x < end_x {
+                row[start_x..end_x].fill(value);
+            }
+        }
+    }
 }
 
 impl IntoIterator for Buffer {
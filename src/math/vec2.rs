@@ -85,8 +85,7 @@ impl Vector2Ext<u32> for Vector2<u32> {
         let angle_radians = (dot_product / (self_magnitude * other_magnitude)).acos();
 
         // Convert the angle to degrees
-        let angle_degrees = angle_radians.to_degrees() as u32;
-        angle_degrees
+        angle_radians.to_degrees() as u32
     }
 
     fn rotate(&self, angle: u32) -> Vector2<u32> {
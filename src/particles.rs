@@ -0,0 +1,647 @@
+use crate::render::canvas::Canvas;
+use crate::render::color::Color;
+use crate::render::fill::Fill;
+use crate::render::raster::Raster;
+use crate::render::sketch::Drawable;
+use crate::types::Image;
+use mint::Vector2;
+use std::f32::consts::TAU;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Values, which can be interpolated by a [Curve].
+pub trait Lerp: Copy {
+    /// Interpolate between `self` and `other` by `t` from `0.0` to `1.0`.
+    fn lerp(self, other: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    #[inline]
+    fn lerp(self, other: Self, t: f32) -> Self { self + (other - self) * t }
+}
+
+impl Lerp for Color {
+    #[inline]
+    fn lerp(self, other: Self, t: f32) -> Self { Color::lerp(self, other, t) }
+}
+
+/// Value over the lifetime of a particle, interpolated between keys from `0.0` (birth) to `1.0` (death).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Curve<T: Lerp> {
+    keys: Vec<(f32, T)>,
+}
+
+impl<T: Lerp> Curve<T> {
+    /// Create a curve, which always returns `value`.
+    #[inline]
+    pub fn constant(value: T) -> Self {
+        Self {
+            keys: vec![(0.0, value)],
+        }
+    }
+
+    /// Create a curve from `start` at birth to `end` at death.
+    #[inline]
+    pub fn linear(start: T, end: T) -> Self {
+        Self {
+            keys: vec![(0.0, start), (1.0, end)],
+        }
+    }
+
+    /// Add a key with `value` at `t`. Keys are kept sorted.
+    #[inline]
+    pub fn with_key(mut self, t: f32, value: T) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let index = self.keys.partition_point(|(key, _)| *key <= t);
+        self.keys.insert(index, (t, value));
+        self
+    }
+
+    /// Sample the curve at `t`.
+    #[inline]
+    pub fn sample(&self, t: f32) -> T {
+        let index = self.keys.partition_point(|(key, _)| *key <= t);
+
+        match (index.checked_sub(1), self.keys.get(index)) {
+            (Some(prev), Some(next)) => {
+                let (a, b) = (self.keys[prev], *next);
+                let span = b.0 - a.0;
+                if span <= f32::EPSILON {
+                    b.1
+                } else {
+                    a.1.lerp(b.1, (t - a.0) / span)
+                }
+            }
+            (Some(prev), None) => self.keys[prev].1,
+            (None, Some(next)) => next.1,
+            (None, None) => unreachable!("curves always have a key"),
+        }
+    }
+}
+
+/// Where an [Emitter] spawns particles, relative to its position.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EmitterShape {
+    /// Spawn every particle at the position.
+    Point,
+    /// Spawn particles anywhere in a rectangle centered on the position.
+    Area { width: f32, height: f32 },
+}
+
+/// When an [Emitter] spawns particles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Emission {
+    /// Spawn `count` particles at once, the next time the system updates.\
+    /// Use [ParticleSystem::burst] to trigger it again.
+    Burst(u32),
+    /// Spawn `rate` particles per second.
+    Continuous(f32),
+}
+
+/// How particles are drawn.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParticleShape {
+    /// Circle with the size as radius.
+    Circle,
+    /// Square with the size as width and height.
+    Rect,
+    /// Image of `width` and `height`, centered on the particle.\
+    /// Sprites are drawn unscaled and only take the alpha of the color curve into account.
+    /// Use [ParticleShape::sprite] to check the size of `data`, sprites with too little data are skipped.
+    Sprite {
+        data: Arc<Image>,
+        width: u32,
+        height: u32,
+    },
+}
+
+impl ParticleShape {
+    /// Create a sprite shape from image `data` with `width` and `height`.
+    /// Returns [None] if the data doesn't match the size.
+    #[inline]
+    pub fn sprite(data: impl Into<Arc<Image>>, width: u32, height: u32) -> Option<Self> {
+        let data = data.into();
+        if data.len() != width as usize * height as usize {
+            return None;
+        }
+
+        Some(ParticleShape::Sprite {
+            data,
+            width,
+            height,
+        })
+    }
+}
+
+/// Spawns particles with randomized velocities and lifetimes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Emitter {
+    pos: Vector2<f32>,
+    shape: EmitterShape,
+    emission: Emission,
+    speed: Range<f32>,
+    direction: Range<f32>,
+    lifetime: Range<f32>,
+    color: Curve<Color>,
+    size: Curve<f32>,
+    particle: ParticleShape,
+    active: bool,
+    pending: f32,
+}
+
+impl Emitter {
+    /// Create a new emitter at `pos`, which spawns white circles in every direction.
+    #[inline]
+    pub fn new(pos: Vector2<f32>, emission: Emission) -> Self {
+        Self {
+            pos,
+            shape: EmitterShape::Point,
+            pending: match emission {
+                Emission::Burst(count) => count as f32,
+                Emission::Continuous(_) => 0.0,
+            },
+            emission,
+            speed: 20.0..60.0,
+            direction: 0.0..TAU,
+            lifetime: 0.5..1.5,
+            color: Curve::linear(Color::WHITE, Color::WHITE.with_alpha(0)),
+            size: Curve::constant(2.0),
+            particle: ParticleShape::Circle,
+            active: true,
+        }
+    }
+
+    /// Set the [EmitterShape].
+    #[inline]
+    pub fn with_shape(mut self, shape: EmitterShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Set the range of initial speeds in pixels per second.
+    #[inline]
+    pub fn with_speed(mut self, speed: Range<f32>) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Set the range of initial directions in radians, where `0.0` points right and `PI / 2` points down.
+    #[inline]
+    pub fn with_direction(mut self, direction: Range<f32>) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Set the range of lifetimes in seconds.
+    #[inline]
+    pub fn with_lifetime(mut self, lifetime: Range<f32>) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    /// Set the color over the lifetime of a particle.
+    #[inline]
+    pub fn with_color(mut self, color: Curve<Color>) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Set the size over the lifetime of a particle.
+    #[inline]
+    pub fn with_size(mut self, size: Curve<f32>) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Set the [ParticleShape].
+    #[inline]
+    pub fn with_particle(mut self, particle: ParticleShape) -> Self {
+        self.particle = particle;
+        self
+    }
+
+    /// Move the emitter to `pos`.
+    #[inline]
+    pub fn set_pos(&mut self, pos: Vector2<f32>) { self.pos = pos; }
+
+    /// Start or stop spawning particles. Living particles are not affected.
+    #[inline]
+    pub fn set_active(&mut self, active: bool) { self.active = active; }
+
+    /// Returns `true` if the emitter spawns particles.
+    #[inline]
+    pub fn is_active(&self) -> bool { self.active }
+}
+
+/// A single particle. Its look is defined by the [Emitter] which spawned it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Particle {
+    pub pos: Vector2<f32>,
+    pub velocity: Vector2<f32>,
+    /// Seconds since the particle got spawned.
+    pub age: f32,
+    /// Seconds the particle lives.
+    pub lifetime: f32,
+    emitter: usize,
+}
+
+/// Simulates and draws particles of multiple [Emitter]s.
+///
+/// Call [ParticleSystem::update] with the fixed time step in [crate::scene::Scene::fixed_update]
+/// and draw the system in [crate::scene::Scene::update].
+/// Particles are stored in one flat list and drawn in a single pass, so thousands of them stay cheap.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParticleSystem {
+    emitters: Vec<Emitter>,
+    particles: Vec<Particle>,
+    gravity: Vector2<f32>,
+    drag: f32,
+    max_particles: usize,
+    seed: u32,
+}
+
+impl Default for ParticleSystem {
+    #[inline]
+    fn default() -> Self { Self::new() }
+}
+
+impl ParticleSystem {
+    /// Create a new system without gravity and drag, which holds up to 10000 particles.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            emitters: Vec::new(),
+            particles: Vec::new(),
+            gravity: Vector2::from([0.0, 0.0]),
+            drag: 0.0,
+            max_particles: 10_000,
+            seed: 0x9E37_79B9,
+        }
+    }
+
+    /// Set the gravity in pixels per second squared, e.g. `vec2(0.0, 98.0)` pulls particles down.
+    #[inline]
+    pub fn with_gravity(mut self, gravity: Vector2<f32>) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Set the drag, which slows particles down by this fraction of their velocity per second.
+    #[inline]
+    pub fn with_drag(mut self, drag: f32) -> Self {
+        self.drag = drag.max(0.0);
+        self
+    }
+
+    /// Set the maximum number of living particles. New particles are not spawned above it.
+    #[inline]
+    pub fn with_max_particles(mut self, max: usize) -> Self {
+        self.max_particles = max;
+        self
+    }
+
+    /// Set the seed of the random number generator, to get the same particles every run.
+    #[inline]
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = seed.max(1);
+        self
+    }
+
+    /// Add `emitter` to the system.
+    #[inline]
+    pub fn with_emitter(mut self, emitter: Emitter) -> Self {
+        self.add_emitter(emitter);
+        self
+    }
+
+    /// Add `emitter` to the system and return its index.
+    #[inline]
+    pub fn add_emitter(&mut self, emitter: Emitter) -> usize {
+        self.emitters.push(emitter);
+        self.emitters.len() - 1
+    }
+
+    /// Get the emitter at `index` as mutable, e.g. to move it.
+    #[inline]
+    pub fn emitter_mut(&mut self, index: usize) -> Option<&mut Emitter> {
+        self.emitters.get_mut(index)
+    }
+
+    /// Spawn the particles of the burst emitter at `index` again, the next time the system updates.\
+    /// Returns [None] if the emitter does not exist or isn't a burst emitter.
+    #[inline]
+    pub fn burst(&mut self, index: usize) -> Option<()> {
+        let emitter = self.emitters.get_mut(index)?;
+        match emitter.emission {
+            Emission::Burst(count) => {
+                emitter.pending += count as f32;
+                Some(())
+            }
+            Emission::Continuous(_) => None,
+        }
+    }
+
+    /// Returns all living particles.
+    #[inline]
+    pub fn particles(&self) -> &[Particle] { &self.particles }
+
+    /// Returns the number of living particles.
+    #[inline]
+    pub fn len(&self) -> usize { self.particles.len() }
+
+    /// Returns `true` if no particles are alive.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.particles.is_empty() }
+
+    /// Remove all living particles.
+    #[inline]
+    pub fn clear(&mut self) { self.particles.clear(); }
+
+    /// Spawn new particles and move, age and remove the living ones by `delta`.
+    #[inline]
+    pub fn update(&mut self, delta: Duration) {
+        let dt = delta.as_secs_f32();
+
+        // move and age living particles
+        let damping = (1.0 - self.drag * dt).max(0.0);
+        for particle in &mut self.particles {
+            particle.velocity.x = (particle.velocity.x + self.gravity.x * dt) * damping;
+            particle.velocity.y = (particle.velocity.y + self.gravity.y * dt) * damping;
+            particle.pos.x += particle.velocity.x * dt;
+            particle.pos.y += particle.velocity.y * dt;
+            particle.age += dt;
+        }
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime);
+
+        // spawn new particles
+        for (index, emitter) in self.emitters.iter_mut().enumerate() {
+            if !emitter.active {
+                continue;
+            }
+
+            if let Emission::Continuous(rate) = emitter.emission {
+                emitter.pending += rate * dt;
+            }
+
+            while emitter.pending >= 1.0 {
+                emitter.pending -= 1.0;
+                if self.particles.len() >= self.max_particles {
+                    continue;
+                }
+
+                let particle = spawn(&mut self.seed, emitter, index);
+                self.particles.push(particle);
+            }
+        }
+    }
+
+    /// Rasterize all particles into `raster`.\
+    /// Particles are small, so drawing them serially is faster than splitting the canvas into bands.
+    #[inline]
+    fn rasterize(&self, raster: &mut Raster) {
+        let rows = raster.rows();
+
+        for particle in &self.particles {
+            let emitter = &self.emitters[particle.emitter];
+            let t = particle.age / particle.lifetime;
+            let color = emitter.color.sample(t);
+            let size = emitter.size.sample(t).max(0.0);
+            let (x, y) = (particle.pos.x, particle.pos.y);
+
+            if color.alpha() == 0 {
+                continue;
+            }
+
+            // skip particles completely outside of the canvas or the rows quickly
+            let (reach_x, reach_y) = match &emitter.particle {
+                ParticleShape::Sprite { width, height, .. } => (*width as f32, *height as f32),
+                _ => (size, size),
+            };
+            if x + reach_x < 0.0
+                || x - reach_x >= raster.width() as f32
+                || y + reach_y < rows.start as f32
+                || y - reach_y >= rows.end as f32
+            {
+                continue;
+            }
+
+            let fill = Fill::Solid(color);
+            match &emitter.particle {
+                ParticleShape::Circle => {
+                    let radius = size.round() as i64;
+                    raster.fill_circle(x.floor() as i64, y.floor() as i64, radius, &fill);
+                }
+                ParticleShape::Rect => {
+                    let half = size / 2.0;
+                    let side = size.round().max(1.0) as i64;
+                    let (left, top) = ((x - half).floor() as i64, (y - half).floor() as i64);
+                    raster.fill_rect(left, top, side, side, &fill);
+                }
+                ParticleShape::Sprite {
+                    data,
+                    width,
+                    height,
+                } => sprite(raster, data, *width, *height, x, y, color.alpha()),
+            }
+        }
+    }
+}
+
+impl Drawable for ParticleSystem {
+    /// Draw all living particles in spawn order.
+    #[inline]
+    fn apply(&self, canvas: &mut Canvas) { self.rasterize(&mut canvas.raster()); }
+}
+
+/// Spawn a particle of `emitter` with random values from `seed`.
+#[inline]
+fn spawn(seed: &mut u32, emitter: &Emitter, index: usize) -> Particle {
+    let mut pos = emitter.pos;
+    if let EmitterShape::Area { width, height } = emitter.shape {
+        pos.x += (random(seed) - 0.5) * width;
+        pos.y += (random(seed) - 0.5) * height;
+    }
+
+    let speed = sample(seed, &emitter.speed);
+    let direction = sample(seed, &emitter.direction);
+
+    Particle {
+        pos,
+        velocity: Vector2::from([direction.cos() * speed, direction.sin() * speed]),
+        age: 0.0,
+        lifetime: sample(seed, &emitter.lifetime).max(f32::EPSILON),
+        emitter: index,
+    }
+}
+
+/// Blend a sprite centered at `x` and `y`, multiplying its alpha with `alpha`.\
+/// Sprites with less `data` than `width * height` pixels are skipped.
+#[inline]
+fn sprite(raster: &mut Raster, data: &Image, width: u32, height: u32, x: f32, y: f32, alpha: u8) {
+    let (width, height) = (width as usize, height as usize);
+    if data.len() < width * height {
+        return;
+    }
+
+    let left = (x - width as f32 / 2.0).floor() as i64;
+    let top = (y - height as f32 / 2.0).floor() as i64;
+
+    // clip sprites, which start left of or above the canvas
+    let skip_x = (-left).clamp(0, width as i64) as usize;
+    let skip_y = (-top).clamp(0, height as i64) as usize;
+    let start_y = top.max(0) as usize;
+    let mut row = Vec::with_capacity(width);
+
+    for y in raster.clip_rows(start_y, (top + height as i64).max(0) as usize) {
+        let offset = (y - start_y + skip_y) * width;
+
+        row.clear();
+        row.extend(
+            data[offset + skip_x..offset + width]
+                .iter()
+                .map(|color| color.with_alpha((color.alpha() as u32 * alpha as u32 / 255) as u8)),
+        );
+
+        raster.blend_span(y, (left + skip_x as i64) as usize, &row);
+    }
+}
+
+/// Get a random number from `0.0` to `1.0` (exclusive) using xorshift.
+#[inline]
+fn random(seed: &mut u32) -> f32 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 17;
+    *seed ^= *seed << 5;
+    (*seed >> 8) as f32 / (1 << 24) as f32
+}
+
+/// Get a random number in `range`.
+#[inline]
+fn sample(seed: &mut u32, range: &Range<f32>) -> f32 {
+    range.start + random(seed) * (range.end - range.start)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::particles::{Curve, Emission, Emitter, ParticleShape, ParticleSystem};
+    use crate::render::canvas::Canvas;
+    use crate::render::color::Color;
+    use crate::simplified::vec2;
+    use std::time::Duration;
+
+    #[test]
+    fn curve_interpolates_keys() {
+        let curve = Curve::linear(0.0, 10.0).with_key(0.5, 2.0);
+
+        assert_eq!(curve.sample(-1.0), 0.0);
+        assert_eq!(curve.sample(0.25), 1.0);
+        assert_eq!(curve.sample(0.75), 6.0);
+        assert_eq!(curve.sample(2.0), 10.0);
+        assert_eq!(Curve::constant(Color::RED).sample(0.3), Color::RED);
+    }
+
+    #[test]
+    fn emits_moves_and_expires() {
+        let step = Duration::from_millis(100);
+        let mut system = ParticleSystem::new()
+            .with_gravity(vec2(0.0, 100.0))
+            .with_emitter(
+                Emitter::new(vec2(50.0, 50.0), Emission::Burst(20))
+                    .with_speed(0.0..0.0)
+                    .with_lifetime(1.0..1.0),
+            )
+            .with_emitter(Emitter::new(vec2(10.0, 10.0), Emission::Continuous(30.0)));
+
+        system.update(step);
+        assert_eq!(system.len(), 20 + 3);
+
+        system.update(step);
+        let falling = &system.particles()[0];
+        assert_eq!(falling.pos.x, 50.0);
+        assert!(falling.pos.y > 50.0);
+
+        // the burst particles die after one second, the continuous emitter keeps spawning
+        for _ in 0..10 {
+            system.update(step);
+        }
+        assert!(system.particles().iter().all(|p| p.emitter == 1));
+        assert!(system.len() < 20 + 3 * 12);
+
+        system.burst(0).unwrap();
+        assert_eq!(system.burst(1), None);
+
+        let mut canvas = Canvas::new(100, 100);
+        canvas.drawable(&system);
+    }
+
+    #[test]
+    fn respects_max_particles() {
+        let mut system = ParticleSystem::new()
+            .with_max_particles(5)
+            .with_emitter(Emitter::new(vec2(0.0, 0.0), Emission::Burst(100)));
+
+        system.update(Duration::from_millis(10));
+        assert_eq!(system.len(), 5);
+    }
+
+    /// Draw one resting particle of `shape` at `x` and `y` onto a black 4x4 canvas.
+    fn draw_one(shape: ParticleShape, x: f32, y: f32) -> Vec<u32> {
+        let mut system = ParticleSystem::new()
+            .with_gravity(vec2(0.0, 0.0))
+            .with_emitter(
+                Emitter::new(vec2(x, y), Emission::Burst(1))
+                    .with_speed(0.0..0.0)
+                    .with_lifetime(10.0..10.0)
+                    .with_color(Curve::constant(Color::WHITE))
+                    .with_size(Curve::constant(2.0))
+                    .with_particle(shape),
+            );
+        system.update(Duration::from_millis(1));
+
+        let mut canvas = Canvas::new(4, 4);
+        canvas.clear(&Color::BLACK);
+        canvas.drawable(&system);
+        canvas.get_buffer().as_slice().to_vec()
+    }
+
+    #[test]
+    fn clips_particles_at_the_edges() {
+        let (w, b) = (Color::WHITE.0, Color::BLACK.0);
+        let sprite = vec![Color::RED, Color::GREEN, Color::BLUE, Color::WHITE];
+        let sprite = ParticleShape::sprite(sprite, 2, 2).unwrap();
+
+        // only the bottom right pixel of the sprite is inside of the canvas
+        let pixels = draw_one(sprite.clone(), 0.5, 0.5);
+        assert_eq!(&pixels[..2], &[w, b]);
+        assert_eq!(pixels[4], b);
+
+        let pixels = draw_one(sprite, 4.5, 4.5);
+        assert_eq!(pixels[15], Color::RED.0);
+        assert_eq!(pixels[14], b);
+
+        let pixels = draw_one(ParticleShape::Rect, 0.0, 0.0);
+        assert_eq!(&pixels[..2], &[w, b]);
+        assert_eq!(pixels[4], b);
+
+        let pixels = draw_one(ParticleShape::Circle, -1.0, 0.0);
+        assert_eq!(pixels[0], w);
+    }
+
+    #[test]
+    fn skips_sprites_with_short_data() {
+        assert_eq!(ParticleShape::sprite(vec![Color::RED], 2, 2), None);
+
+        let short = ParticleShape::Sprite {
+            data: vec![Color::RED].into(),
+            width: 2,
+            height: 2,
+        };
+        for (x, y) in [(0.0, 0.0), (2.0, 2.0), (4.0, 4.0)] {
+            assert!(draw_one(short.clone(), x, y)
+                .iter()
+                .all(|px| *px == Color::BLACK.0));
+        }
+    }
+}
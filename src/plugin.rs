@@ -2,6 +2,11 @@ use crate::game::context::GameContext;
 
 use crate::render::canvas::Canvas;
 
+/// Contains the built-in [recorder::RecorderPlugin].
+pub mod recorder;
+/// Contains the built-in [screenshot::ScreenshotPlugin].
+pub mod screenshot;
+
 pub trait QPlugin {
     /// Gets called before the game enters the main loop.
     fn on_enter(&mut self, canvas: &mut Canvas);
@@ -12,6 +17,26 @@ pub trait QPlugin {
     /// Gets called after the game calls the [Scene::update] method.
     fn post_update(&mut self, canvas: &mut Canvas, ctx: &mut GameContext);
 
+    /// Gets called every frame right before presenting, after post-processing.\
+    /// `canvas` contains the final frame, which is also passed if nothing changed since the last frame.
+    /// Everything drawn to it gets presented.
+    /// Does nothing by default.
+    fn pre_present(&mut self, _canvas: &mut Canvas, _ctx: &mut GameContext) {}
+
     /// Gets called when the game is exiting.
     fn on_exit(&mut self, canvas: &mut Canvas);
 }
+
+/// Create a new empty directory in the temp directory, which no other test uses.
+#[cfg(test)]
+pub(crate) fn test_directory(name: &str) -> std::path::PathBuf {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+    let count = COUNT.fetch_add(1, Ordering::Relaxed);
+    let directory =
+        std::env::temp_dir().join(format!("qilin-{name}-{}-{count}", std::process::id()));
+
+    let _ = std::fs::remove_dir_all(&directory);
+    directory
+}
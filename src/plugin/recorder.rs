@@ -0,0 +1,470 @@
+use crate::game::context::GameContext;
+use crate::plugin::screenshot::{timestamped, ScreenshotFormat};
+use crate::plugin::QPlugin;
+use crate::render::canvas::Canvas;
+use minifb::{Key, KeyRepeat};
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// No pending command of a [RecorderHandle].
+const NONE: u8 = 0;
+/// A [RecorderHandle] requested to start recording.
+const START: u8 = 1;
+/// A [RecorderHandle] requested to stop recording.
+const STOP: u8 = 2;
+
+/// Output format of a recording.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RecordFormat {
+    /// Numbered image files in a new directory, e.g. `00000.png`, `00001.png`, ...
+    Sequence(ScreenshotFormat),
+    /// A single looping animated GIF. Requires `image` feature.
+    #[cfg(feature = "image")]
+    Gif,
+}
+
+impl Default for RecordFormat {
+    /// Returns [RecordFormat::Gif] with `image` feature, otherwise a PPM [RecordFormat::Sequence].
+    #[inline]
+    fn default() -> Self {
+        #[cfg(feature = "image")]
+        return RecordFormat::Gif;
+
+        #[cfg(not(feature = "image"))]
+        return RecordFormat::Sequence(ScreenshotFormat::Ppm);
+    }
+}
+
+/// Controls a [RecorderPlugin] from outside of the plugin, e.g. from a scene.\
+/// Commands are applied at the next presented frame.
+#[derive(Clone, Debug, Default)]
+pub struct RecorderHandle {
+    command: Arc<AtomicU8>,
+    recording: Arc<AtomicBool>,
+}
+
+impl RecorderHandle {
+    /// Start recording, if not recording already.
+    #[inline]
+    pub fn start(&self) { self.command.store(START, Ordering::Relaxed); }
+
+    /// Stop recording, if recording.
+    #[inline]
+    pub fn stop(&self) { self.command.store(STOP, Ordering::Relaxed); }
+
+    /// Returns `true` if frames are currently being recorded.
+    #[inline]
+    pub fn is_recording(&self) -> bool { self.recording.load(Ordering::Relaxed) }
+}
+
+/// A captured frame and how long it was shown.
+struct Frame {
+    canvas: Canvas,
+    // only GIFs store frame delays
+    #[cfg_attr(not(feature = "image"), allow(dead_code))]
+    delay: Duration,
+}
+
+/// A running recording.
+struct Recording {
+    sender: SyncSender<Frame>,
+    started: Instant,
+    last: Option<(Canvas, Instant)>,
+    dropped: usize,
+}
+
+impl std::fmt::Debug for Recording {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Recording")
+            .field("started", &self.started)
+            .field("dropped", &self.dropped)
+            .finish()
+    }
+}
+
+/// Plugin, which records the presented frames and writes them as animated GIF or image sequence.
+///
+/// Frames are encoded and written on a background thread, so the game loop doesn't get stalled.
+/// If the thread can't keep up, frames get dropped instead of blocking the game.\
+/// Recordings start and stop with a hotkey ([Key::F10] by default) or a [RecorderHandle].
+/// Recordings contain the frames after post-processing.
+#[derive(Debug)]
+pub struct RecorderPlugin {
+    key: Option<Key>,
+    directory: PathBuf,
+    prefix: String,
+    format: RecordFormat,
+    duration: Option<Duration>,
+    interval: Duration,
+    queue: usize,
+    on_finish: fn(io::Result<PathBuf>),
+    handle: RecorderHandle,
+    recording: Option<Recording>,
+    workers: Vec<JoinHandle<()>>,
+    last_stem: Option<(String, usize)>,
+}
+
+impl Default for RecorderPlugin {
+    #[inline]
+    fn default() -> Self { Self::new() }
+}
+
+impl RecorderPlugin {
+    /// Create a new plugin, which records at 30 frames per second while toggled with [Key::F10].
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            key: Some(Key::F10),
+            directory: PathBuf::from("."),
+            prefix: "recording".to_string(),
+            format: RecordFormat::default(),
+            duration: None,
+            interval: Duration::from_secs_f32(1.0 / 30.0),
+            queue: 64,
+            on_finish: |_| {},
+            handle: RecorderHandle::default(),
+            recording: None,
+            workers: Vec::new(),
+            last_stem: None,
+        }
+    }
+
+    /// Set the key, which toggles recording. Use [None] to only control recording with a [RecorderHandle].
+    #[inline]
+    pub fn with_key(mut self, key: Option<Key>) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// Set the directory to save recordings in. Gets created if it doesn't exist.
+    #[inline]
+    pub fn with_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = directory.into();
+        self
+    }
+
+    /// Set the prefix of the file or directory names.
+    #[inline]
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Set the [RecordFormat] of recordings.
+    #[inline]
+    pub fn with_format(mut self, format: RecordFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Stop recordings automatically after `duration`. Use [None] to record until stopped.
+    #[inline]
+    pub fn with_duration(mut self, duration: Option<Duration>) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Set how many frames per second get recorded at most.
+    #[inline]
+    pub fn with_fps(mut self, fps: f32) -> Self {
+        self.interval = Duration::from_secs_f32(1.0 / fps.max(1.0));
+        self
+    }
+
+    /// Set how many frames may wait for the background thread before frames get dropped.
+    #[inline]
+    pub fn with_queue(mut self, queue: usize) -> Self {
+        self.queue = queue.max(1);
+        self
+    }
+
+    /// Set the function, which gets called on the background thread with the path or error
+    /// after every recording. Does nothing by default.
+    #[inline]
+    pub fn with_on_finish(mut self, on_finish: fn(io::Result<PathBuf>)) -> Self {
+        self.on_finish = on_finish;
+        self
+    }
+
+    /// Get a [RecorderHandle] to start and stop recording after the plugin got added to the game.
+    #[inline]
+    pub fn handle(&self) -> RecorderHandle { self.handle.clone() }
+
+    /// Returns `true` if frames are currently being recorded.
+    #[inline]
+    pub fn is_recording(&self) -> bool { self.recording.is_some() }
+
+    /// Start a new recording and its background thread. Does nothing if already recording.
+    #[inline]
+    pub fn start(&mut self) {
+        if self.recording.is_some() {
+            return;
+        }
+
+        let (sender, receiver) = mpsc::sync_channel(self.queue);
+        let path = self.next_path();
+        let format = self.format;
+        let on_finish = self.on_finish;
+
+        self.workers.retain(|worker| !worker.is_finished());
+        self.workers.push(std::thread::spawn(move || {
+            on_finish(write(receiver, path, format));
+        }));
+
+        self.recording = Some(Recording {
+            sender,
+            started: Instant::now(),
+            last: None,
+            dropped: 0,
+        });
+        self.handle.recording.store(true, Ordering::Relaxed);
+    }
+
+    /// Stop the current recording. The background thread finishes writing the queued frames.\
+    /// Like every other frame, the last one gets dropped if the queue is full.
+    #[inline]
+    pub fn stop(&mut self) {
+        if let Some(mut recording) = self.recording.take() {
+            // the last frame gets sent once its delay is known
+            if let Some((canvas, time)) = recording.last.take() {
+                let delay = time.elapsed().min(self.interval);
+                let _ = recording.sender.try_send(Frame { canvas, delay });
+            }
+        }
+        self.handle.recording.store(false, Ordering::Relaxed);
+    }
+
+    /// Get a timestamped path for a new recording, which doesn't overwrite an earlier one.
+    fn next_path(&mut self) -> PathBuf {
+        let stem = timestamped(&self.prefix);
+
+        // recordings started in the same millisecond get a counter,
+        // since earlier ones may not be written yet
+        let mut count = match &self.last_stem {
+            Some((last, count)) if *last == stem => count + 1,
+            _ => 0,
+        };
+        let named = |count: usize| {
+            match count {
+                0 => self.directory.join(&stem),
+                _ => self.directory.join(format!("{stem}-{count}")),
+            }
+        };
+
+        let mut path = named(count);
+        while path.exists() || path.with_extension("gif").exists() {
+            count += 1;
+            path = named(count);
+        }
+
+        self.last_stem = Some((stem, count));
+        path
+    }
+
+    /// Capture `canvas` if the frame interval passed.
+    #[inline]
+    fn capture(&mut self, canvas: &Canvas) {
+        let Some(recording) = &mut self.recording else {
+            return;
+        };
+
+        let now = Instant::now();
+        if let Some((_, time)) = &recording.last {
+            if now - *time < self.interval {
+                return;
+            }
+        }
+
+        if let Some((canvas, time)) = recording.last.replace((canvas.clone(), now)) {
+            let frame = Frame {
+                canvas,
+                delay: now - time,
+            };
+
+            if let Err(TrySendError::Full(_)) = recording.sender.try_send(frame) {
+                recording.dropped += 1;
+            }
+        }
+
+        if let Some(duration) = self.duration {
+            if now - recording.started >= duration {
+                self.stop();
+            }
+        }
+    }
+}
+
+impl QPlugin for RecorderPlugin {
+    fn on_enter(&mut self, _canvas: &mut Canvas) {}
+
+    fn pre_update(&mut self, _canvas: &mut Canvas, _ctx: &mut GameContext) {}
+
+    fn post_update(&mut self, _canvas: &mut Canvas, _ctx: &mut GameContext) {}
+
+    fn pre_present(&mut self, canvas: &mut Canvas, ctx: &mut GameContext) {
+        let toggled = self
+            .key
+            .is_some_and(|key| ctx.is_key_pressed(key, KeyRepeat::No));
+
+        match self.handle.command.swap(NONE, Ordering::Relaxed) {
+            START => self.start(),
+            STOP => self.stop(),
+            _ if toggled && self.is_recording() => self.stop(),
+            _ if toggled => self.start(),
+            _ => {}
+        }
+
+        self.capture(canvas);
+    }
+
+    fn on_exit(&mut self, _canvas: &mut Canvas) {
+        self.stop();
+
+        // wait for all recordings to be written
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Write all frames received from `receiver` to `path` in `format`.
+fn write(receiver: Receiver<Frame>, path: PathBuf, format: RecordFormat) -> io::Result<PathBuf> {
+    match format {
+        RecordFormat::Sequence(format) => {
+            std::fs::create_dir_all(&path)?;
+
+            for (i, frame) in receiver.into_iter().enumerate() {
+                let file = path.join(format!("{i:05}.{}", format.extension()));
+                format.save(&frame.canvas, file)?;
+            }
+
+            Ok(path)
+        }
+        #[cfg(feature = "image")]
+        RecordFormat::Gif => {
+            let path = path.with_extension("gif");
+            write_gif(receiver, &path)?;
+            Ok(path)
+        }
+    }
+}
+
+/// Encode all frames received from `receiver` as looping GIF to `path`.
+#[cfg(feature = "image")]
+fn write_gif(receiver: Receiver<Frame>, path: &std::path::Path) -> io::Result<()> {
+    use image::codecs::gif::{GifEncoder, Repeat};
+    use image::Delay;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let file = io::BufWriter::new(std::fs::File::create(path)?);
+    let mut encoder = GifEncoder::new_with_speed(file, 10);
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .map_err(io::Error::other)?;
+
+    for frame in receiver {
+        let image = crate::image::canvas_to_rgba(&frame.canvas);
+        let delay = Delay::from_saturating_duration(frame.delay);
+        encoder
+            .encode_frame(image::Frame::from_parts(image, 0, 0, delay))
+            .map_err(io::Error::other)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plugin::recorder::{RecordFormat, RecorderPlugin};
+    use crate::plugin::screenshot::ScreenshotFormat;
+    use crate::plugin::{test_directory, QPlugin};
+    use crate::render::canvas::Canvas;
+    use crate::render::color::Color;
+
+    #[test]
+    fn records_sequence_in_background() {
+        let directory = test_directory("recorder");
+        let mut plugin = RecorderPlugin::new()
+            .with_directory(&directory)
+            .with_format(RecordFormat::Sequence(ScreenshotFormat::Ppm))
+            .with_fps(f32::INFINITY);
+        let handle = plugin.handle();
+        let mut canvas = Canvas::new(4, 4);
+
+        plugin.start();
+        assert!(handle.is_recording());
+        for color in [Color::RED, Color::GREEN, Color::BLUE] {
+            canvas.clear(&color);
+            plugin.capture(&canvas);
+        }
+        // waits for the background thread to finish writing
+        plugin.on_exit(&mut canvas);
+        assert!(!handle.is_recording());
+
+        let recording = std::fs::read_dir(&directory)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        assert_eq!(std::fs::read_dir(recording).unwrap().count(), 3);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn never_reuses_recording_paths() {
+        let directory = test_directory("recorder-paths");
+        let mut plugin = RecorderPlugin::new()
+            .with_directory(&directory)
+            .with_format(RecordFormat::Sequence(ScreenshotFormat::Ppm));
+        let mut canvas = Canvas::new(2, 2);
+
+        for _ in 0..3 {
+            plugin.start();
+            plugin.capture(&canvas);
+            plugin.stop();
+        }
+        plugin.on_exit(&mut canvas);
+
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 3);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn records_gif() {
+        let directory = test_directory("recorder-gif");
+        let mut plugin = RecorderPlugin::new()
+            .with_directory(&directory)
+            .with_format(RecordFormat::Gif)
+            .with_fps(f32::INFINITY);
+        let mut canvas = Canvas::new(8, 8);
+
+        plugin.start();
+        for color in [Color::RED, Color::BLUE] {
+            canvas.clear(&color);
+            plugin.capture(&canvas);
+        }
+        plugin.on_exit(&mut canvas);
+
+        let recording = std::fs::read_dir(&directory)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        assert_eq!(recording.extension().unwrap(), "gif");
+        assert!(std::fs::read(recording).unwrap().starts_with(b"GIF89a"));
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,212 @@
+use crate::game::context::GameContext;
+use crate::plugin::QPlugin;
+use crate::render::canvas::Canvas;
+use minifb::{Key, KeyRepeat};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// File format of screenshots.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ScreenshotFormat {
+    /// Binary PPM, which works without any features.
+    Ppm,
+    /// PNG. Requires `image` feature.
+    #[cfg(feature = "image")]
+    Png,
+    /// BMP. Requires `image` feature.
+    #[cfg(feature = "image")]
+    Bmp,
+}
+
+impl Default for ScreenshotFormat {
+    /// Returns [ScreenshotFormat::Png] with `image` feature, otherwise [ScreenshotFormat::Ppm].
+    #[inline]
+    fn default() -> Self {
+        #[cfg(feature = "image")]
+        return ScreenshotFormat::Png;
+
+        #[cfg(not(feature = "image"))]
+        return ScreenshotFormat::Ppm;
+    }
+}
+
+impl ScreenshotFormat {
+    /// Returns the file extension of the format.
+    #[inline]
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ScreenshotFormat::Ppm => "ppm",
+            #[cfg(feature = "image")]
+            ScreenshotFormat::Png => "png",
+            #[cfg(feature = "image")]
+            ScreenshotFormat::Bmp => "bmp",
+        }
+    }
+
+    /// Save `canvas` to `path` in this format.
+    #[inline]
+    pub fn save(&self, canvas: &Canvas, path: impl AsRef<Path>) -> io::Result<()> {
+        match self {
+            ScreenshotFormat::Ppm => canvas.save_ppm(path),
+            #[cfg(feature = "image")]
+            ScreenshotFormat::Png => canvas.save_png(path).map_err(io::Error::other),
+            #[cfg(feature = "image")]
+            ScreenshotFormat::Bmp => canvas.save_bmp(path).map_err(io::Error::other),
+        }
+    }
+}
+
+/// Plugin, which saves the presented frame to a timestamped file when a key gets pressed.
+///
+/// Files are named `<prefix>-<unix seconds>-<milliseconds>.<extension>` and are written to the
+/// current directory by default. Screenshots contain the frame after post-processing.
+#[derive(Clone, Debug)]
+pub struct ScreenshotPlugin {
+    key: Key,
+    directory: PathBuf,
+    prefix: String,
+    format: ScreenshotFormat,
+    on_capture: fn(io::Result<PathBuf>),
+}
+
+impl Default for ScreenshotPlugin {
+    #[inline]
+    fn default() -> Self { Self::new() }
+}
+
+impl ScreenshotPlugin {
+    /// Create a new plugin, which saves a screenshot when pressing [Key::F12].
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            key: Key::F12,
+            directory: PathBuf::from("."),
+            prefix: "screenshot".to_string(),
+            format: ScreenshotFormat::default(),
+            on_capture: |_| {},
+        }
+    }
+
+    /// Set the key, which saves a screenshot.
+    #[inline]
+    pub fn with_key(mut self, key: Key) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// Set the directory to save screenshots in. Gets created if it doesn't exist.
+    #[inline]
+    pub fn with_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = directory.into();
+        self
+    }
+
+    /// Set the prefix of the file names.
+    #[inline]
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Set the [ScreenshotFormat] of the files.
+    #[inline]
+    pub fn with_format(mut self, format: ScreenshotFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the function, which gets called with the path or error after every screenshot.\
+    /// Does nothing by default.
+    #[inline]
+    pub fn with_on_capture(mut self, on_capture: fn(io::Result<PathBuf>)) -> Self {
+        self.on_capture = on_capture;
+        self
+    }
+
+    /// Save `canvas` to a new timestamped file and return its path.
+    #[inline]
+    pub fn capture(&self, canvas: &Canvas) -> io::Result<PathBuf> {
+        std::fs::create_dir_all(&self.directory)?;
+
+        let stem = timestamped(&self.prefix);
+        let extension = self.format.extension();
+
+        // don't overwrite screenshots taken in the same millisecond
+        let mut path = self.directory.join(format!("{stem}.{extension}"));
+        let mut count = 1;
+        while path.exists() {
+            path = self.directory.join(format!("{stem}-{count}.{extension}"));
+            count += 1;
+        }
+
+        self.format.save(canvas, &path)?;
+        Ok(path)
+    }
+}
+
+impl QPlugin for ScreenshotPlugin {
+    fn on_enter(&mut self, _canvas: &mut Canvas) {}
+
+    fn pre_update(&mut self, _canvas: &mut Canvas, _ctx: &mut GameContext) {}
+
+    fn post_update(&mut self, _canvas: &mut Canvas, _ctx: &mut GameContext) {}
+
+    fn pre_present(&mut self, canvas: &mut Canvas, ctx: &mut GameContext) {
+        if ctx.is_key_pressed(self.key, KeyRepeat::No) {
+            (self.on_capture)(self.capture(canvas));
+        }
+    }
+
+    fn on_exit(&mut self, _canvas: &mut Canvas) {}
+}
+
+/// Append the current unix time in seconds and milliseconds to `prefix`.
+#[inline]
+pub(crate) fn timestamped(prefix: &str) -> String {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format!("{prefix}-{}-{:03}", time.as_secs(), time.subsec_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plugin::screenshot::{ScreenshotFormat, ScreenshotPlugin};
+    use crate::plugin::test_directory;
+    use crate::render::canvas::Canvas;
+    use crate::render::color::Color;
+
+    #[test]
+    fn writes_ppm() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas.set_pixel(0, 0, &Color::from_rgb(1, 2, 3));
+        canvas.set_pixel(1, 0, &Color::from_rgba(4, 5, 6, 7));
+
+        let mut bytes = Vec::new();
+        canvas.write_ppm(&mut bytes).unwrap();
+        assert_eq!(bytes, b"P6\n2 1\n255\n\x01\x02\x03\x04\x05\x06");
+    }
+
+    #[test]
+    fn captures_timestamped_files() {
+        let directory = test_directory("screenshot");
+        let plugin = ScreenshotPlugin::new()
+            .with_directory(&directory)
+            .with_format(ScreenshotFormat::Ppm);
+        let canvas = Canvas::new(4, 4);
+
+        let first = plugin.capture(&canvas).unwrap();
+        let second = plugin.capture(&canvas).unwrap();
+        assert_ne!(first, second);
+        assert!(first
+            .file_name()
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("screenshot-"));
+        assert_eq!(std::fs::read(&first).unwrap().len(), 11 + 4 * 4 * 3);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,449 @@
+use crate::render::canvas::Canvas;
+use crate::render::color::Color;
+use crate::render::sketch::Drawable;
+use crate::types::Image;
+use mint::Vector2;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Region of a [BitmapFont] image showing one character, and how it gets placed.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct BitmapGlyph {
+    /// Left edge of the glyph in the font image.
+    pub x: u32,
+    /// Top edge of the glyph in the font image.
+    pub y: u32,
+    /// Width of the glyph in the font image.
+    pub width: u32,
+    /// Height of the glyph in the font image.
+    pub height: u32,
+    /// Horizontal offset from the pen position to the left edge of the glyph.
+    pub x_offset: i32,
+    /// Vertical offset from the top of the line to the top edge of the glyph.
+    pub y_offset: i32,
+    /// Distance to move the pen after drawing the glyph.
+    pub advance: i32,
+}
+
+/// Font made of glyphs cut out of a single image, which doesn't need any optional feature.
+///
+/// Create one from an image with equally sized cells using [BitmapFont::from_grid]
+/// or from an [AngelCode BMFont](https://www.angelcode.com/products/bmfont/) descriptor
+/// using [BitmapFont::from_bmfont]. Draw text with [BitmapText].
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitmapFont {
+    data: Arc<Image>,
+    width: u32,
+    height: u32,
+    glyphs: HashMap<char, BitmapGlyph>,
+    kerning: HashMap<(char, char), i32>,
+    line_height: u32,
+}
+
+impl BitmapFont {
+    /// Create a new font from image `data` with `width` and `height`, split into cells of
+    /// `cell_width` x `cell_height`, which contain the characters of `chars` row by row.\
+    /// Every glyph advances by the cell width. Returns [None] if the data doesn't match the size,
+    /// a cell is empty or there are more characters than cells.
+    #[inline]
+    pub fn from_grid(
+        data: impl Into<Arc<Image>>,
+        width: u32,
+        height: u32,
+        cell_width: u32,
+        cell_height: u32,
+        chars: &str,
+    ) -> Option<Self> {
+        if cell_width == 0 || cell_height == 0 {
+            return None;
+        }
+
+        let columns = width / cell_width;
+        let cells = columns as usize * (height / cell_height) as usize;
+        if chars.chars().count() > cells {
+            return None;
+        }
+
+        let glyphs = chars
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let (column, row) = (i as u32 % columns, i as u32 / columns);
+                let glyph = BitmapGlyph {
+                    x: column * cell_width,
+                    y: row * cell_height,
+                    width: cell_width,
+                    height: cell_height,
+                    x_offset: 0,
+                    y_offset: 0,
+                    advance: cell_width as i32,
+                };
+                (c, glyph)
+            })
+            .collect();
+
+        Self::new(data, width, height, glyphs, cell_height)
+    }
+
+    /// Create a new font from the text format of a BMFont `descriptor` (`.fnt`) and the image `data`
+    /// of its page with `width` and `height`.\
+    /// Only the first page is supported, glyphs on other pages are skipped.
+    /// Returns [None] if the descriptor has no `common` line, contains invalid or negative numbers
+    /// where sizes or positions are expected, or a glyph is outside of the image.
+    pub fn from_bmfont(
+        descriptor: &str,
+        data: impl Into<Arc<Image>>,
+        width: u32,
+        height: u32,
+    ) -> Option<Self> {
+        let mut glyphs = HashMap::new();
+        let mut kerning = HashMap::new();
+        let mut line_height = None;
+
+        for line in descriptor.lines() {
+            let line = line.trim();
+            let (tag, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let attributes = parse_attributes(rest);
+            let int = |key: &str| -> Option<i32> {
+                attributes
+                    .get(key)
+                    .map_or(Some(0), |value| value.parse().ok())
+            };
+            let uint = |key: &str| u32::try_from(int(key)?).ok();
+            let character = |key: &str| char::from_u32(uint(key)?);
+
+            match tag {
+                "common" => line_height = Some(uint("lineHeight")?),
+                "char" if int("page")? == 0 => {
+                    let glyph = BitmapGlyph {
+                        x: uint("x")?,
+                        y: uint("y")?,
+                        width: uint("width")?,
+                        height: uint("height")?,
+                        x_offset: int("xoffset")?,
+                        y_offset: int("yoffset")?,
+                        advance: int("xadvance")?,
+                    };
+                    glyphs.insert(character("id")?, glyph);
+                }
+                "kerning" => {
+                    kerning.insert((character("first")?, character("second")?), int("amount")?);
+                }
+                _ => {}
+            }
+        }
+
+        let mut font = Self::new(data, width, height, glyphs, line_height?)?;
+        font.kerning = kerning;
+        Some(font)
+    }
+
+    /// Create a font from `glyphs` and check that they are inside of the image.
+    fn new(
+        data: impl Into<Arc<Image>>,
+        width: u32,
+        height: u32,
+        glyphs: HashMap<char, BitmapGlyph>,
+        line_height: u32,
+    ) -> Option<Self> {
+        let data = data.into();
+        let inside = |g: &BitmapGlyph| {
+            g.x.checked_add(g.width).is_some_and(|end| end <= width)
+                && g.y.checked_add(g.height).is_some_and(|end| end <= height)
+        };
+        if data.len() != width as usize * height as usize || !glyphs.values().all(inside) {
+            return None;
+        }
+
+        Some(Self {
+            data,
+            width,
+            height,
+            glyphs,
+            kerning: HashMap::new(),
+            line_height,
+        })
+    }
+
+    /// Move `second` by `amount` pixels, when it follows `first`.
+    #[inline]
+    pub fn with_kerning(mut self, first: char, second: char, amount: i32) -> Self {
+        self.kerning.insert((first, second), amount);
+        self
+    }
+
+    /// Set the distance between the tops of two lines.
+    #[inline]
+    pub fn with_line_height(mut self, line_height: u32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+
+    /// Get the glyph of `c`.
+    #[inline]
+    pub fn glyph(&self, c: char) -> Option<&BitmapGlyph> { self.glyphs.get(&c) }
+
+    /// Get the kerning between `first` and `second`, which is `0` if there is none.
+    #[inline]
+    pub fn kerning(&self, first: char, second: char) -> i32 {
+        self.kerning.get(&(first, second)).copied().unwrap_or(0)
+    }
+
+    /// Get the distance between the tops of two lines.
+    #[inline]
+    pub fn line_height(&self) -> u32 { self.line_height }
+
+    /// Measure the width and height of `text` without scaling.\
+    /// The width is the advance of the longest line.
+    #[inline]
+    pub fn measure(&self, text: &str) -> (u32, u32) {
+        let mut width = 0;
+        self.layout(text, |_, x, _| width = width.max(x));
+        let lines = text.split('\n').count() as u32;
+        (width.max(0) as u32, lines * self.line_height)
+    }
+
+    /// Call `f` with every glyph and its position relative to the start of `text`,
+    /// and finally with [None] and the pen position at the end of every line.
+    fn layout(&self, text: &str, mut f: impl FnMut(Option<&BitmapGlyph>, i32, i32)) {
+        let mut y: i32 = 0;
+        for line in text.split('\n') {
+            let mut x: i32 = 0;
+            let mut previous = None;
+
+            for c in line.chars() {
+                let Some(glyph) = self.glyphs.get(&c) else {
+                    continue;
+                };
+
+                if let Some(previous) = previous {
+                    x = x.saturating_add(self.kerning(previous, c));
+                }
+                f(Some(glyph), x, y);
+
+                x = x.saturating_add(glyph.advance);
+                previous = Some(c);
+            }
+
+            f(None, x, y);
+            y = y.saturating_add(self.line_height as i32);
+        }
+    }
+}
+
+/// Parse `key=value` pairs, where values may be quoted.
+fn parse_attributes(text: &str) -> HashMap<&str, &str> {
+    let mut attributes = HashMap::new();
+    let mut rest = text.trim_start();
+
+    while let Some((key, value)) = rest.split_once('=') {
+        let (value, next) = match value.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => value.split_once(' ').unwrap_or((value, "")),
+        };
+
+        attributes.insert(key.trim(), value);
+        rest = next.trim_start();
+    }
+
+    attributes
+}
+
+/// Text drawn with a [BitmapFont].
+///
+/// Lines are separated by `\n` and characters missing from the font are skipped.
+#[derive(Clone, Debug)]
+pub struct BitmapText<'a> {
+    font: &'a BitmapFont,
+    pos: Vector2<u32>,
+    text: String,
+    tint: Color,
+    scale: u32,
+}
+
+impl<'a> BitmapText<'a> {
+    /// Create new `text` at `pos` drawn with `font`.
+    #[inline]
+    pub fn new(pos: Vector2<u32>, font: &'a BitmapFont, text: impl Into<String>) -> Self {
+        Self {
+            font,
+            pos,
+            text: text.into(),
+            tint: Color::WHITE,
+            scale: 1,
+        }
+    }
+
+    /// Multiply every glyph pixel with `tint`. Use white glyphs to draw text in any color.
+    #[inline]
+    pub fn with_tint(mut self, tint: Color) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// Scale every glyph pixel to `scale` x `scale` pixels. Default is `1`.
+    #[inline]
+    pub fn with_scale(mut self, scale: u32) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Measure the width and height of the text in canvas pixels.
+    #[inline]
+    pub fn measure(&self) -> (u32, u32) {
+        let (width, height) = self.font.measure(&self.text);
+        (
+            width.saturating_mul(self.scale),
+            height.saturating_mul(self.scale),
+        )
+    }
+}
+
+impl Drawable for BitmapText<'_> {
+    /// Blend the glyphs on top of `canvas`.
+    #[inline]
+    fn apply(&self, canvas: &mut Canvas) {
+        let font = self.font;
+        let scale = self.scale as i128;
+        let mut raster = canvas.raster();
+        let canvas_width = raster.width() as i128;
+        let mut row = Vec::new();
+
+        font.layout(&self.text, |glyph, x, y| {
+            let Some(glyph) = glyph else {
+                return;
+            };
+
+            // large scales move glyphs far outside of the canvas, which doesn't overflow in i128
+            let left = self.pos.x as i128 + (x as i128 + glyph.x_offset as i128) * scale;
+            let top = self.pos.y as i128 + (y as i128 + glyph.y_offset as i128) * scale;
+            let right = (left + glyph.width as i128 * scale).min(canvas_width);
+            let bottom = top + glyph.height as i128 * scale;
+
+            // skip glyphs outside of the canvas and clip the others to it
+            if left >= right || bottom <= 0 {
+                return;
+            }
+            let row_of = |y: i128| y.clamp(0, usize::MAX as i128) as usize;
+            let start_x = left.max(0);
+
+            for canvas_y in raster.clip_rows(row_of(top), row_of(bottom)) {
+                let src_y = glyph.y as usize + ((canvas_y as i128 - top) / scale) as usize;
+                let src_row = &font.data[src_y * font.width as usize..];
+
+                row.clear();
+                row.extend((start_x..right).map(|canvas_x| {
+                    let src_x = glyph.x as usize + ((canvas_x - left) / scale) as usize;
+                    src_row[src_x] * self.tint
+                }));
+
+                raster.blend_span(canvas_y, start_x as usize, &row);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::bitmap_font::{BitmapFont, BitmapText};
+    use crate::render::canvas::Canvas;
+    use crate::render::color::Color;
+    use crate::simplified::vec2;
+
+    /// 4x2 grid of 2x2 cells, where `A` is filled and `B` has only its left column.
+    fn grid() -> BitmapFont {
+        let (w, t) = (Color::WHITE, Color::TRANSPARENT);
+        #[rustfmt::skip]
+        let data = vec![
+            w, w, w, t,
+            w, w, w, t,
+        ];
+        BitmapFont::from_grid(data, 4, 2, 2, 2, "AB").unwrap()
+    }
+
+    #[test]
+    fn draws_grid_glyphs_with_tint_and_kerning() {
+        let font = grid().with_kerning('A', 'B', 1);
+        let mut canvas = Canvas::new(6, 2);
+        canvas.drawable(&BitmapText::new(vec2(0, 0), &font, "AB").with_tint(Color::RED));
+
+        let row: Vec<Color> = (0..6).map(|x| canvas.get_pixel(x, 0).unwrap()).collect();
+        let (r, t) = (Color::RED, Color::BLACK);
+        assert_eq!(row, vec![r, r, t, r, t, t]);
+        assert_eq!(font.measure("AB\nA"), (5, 4));
+    }
+
+    #[test]
+    fn scales_glyphs() {
+        let font = grid();
+        let mut canvas = Canvas::new(8, 4);
+        let text = BitmapText::new(vec2(0, 0), &font, "BA").with_scale(2);
+        canvas.drawable(&text);
+
+        assert_eq!(text.measure(), (8, 4));
+        assert_eq!(canvas.get_pixel(1, 3), Some(Color::WHITE));
+        assert_eq!(canvas.get_pixel(2, 3), Some(Color::BLACK));
+        assert_eq!(canvas.get_pixel(7, 3), Some(Color::WHITE));
+    }
+
+    #[test]
+    fn clips_huge_scales() {
+        let font = grid();
+        for scale in [1 << 30, u32::MAX] {
+            let mut canvas = Canvas::new(4, 4);
+            let text = BitmapText::new(vec2(1, 1), &font, "AB\nA").with_scale(scale);
+            let kerned =
+                font.clone()
+                    .with_kerning('A', 'B', i32::MIN)
+                    .with_kerning('B', 'A', i32::MAX);
+            canvas.drawable(&text);
+
+            assert_eq!(text.measure(), (u32::MAX, u32::MAX));
+            assert_eq!(canvas.get_pixel(0, 0), Some(Color::BLACK));
+            assert_eq!(canvas.get_pixel(3, 3), Some(Color::WHITE));
+
+            canvas.drawable(&BitmapText::new(vec2(0, 0), &kerned, "ABA").with_scale(scale));
+        }
+    }
+
+    #[test]
+    fn parses_bmfont_descriptor() {
+        let descriptor = r#"info face="Tiny Font" size=2 bold=0
+common lineHeight=3 base=2 scaleW=4 scaleH=2 pages=1
+page id=0 file="tiny_0.png"
+chars count=3
+char id=65 x=0 y=0 width=2 height=2 xoffset=0 yoffset=1 xadvance=3 page=0 chnl=15
+char id=66 x=2 y=0 width=1 height=2 xoffset=1 yoffset=0 xadvance=2 page=0 chnl=15
+char id=67 x=0 y=0 width=2 height=2 xoffset=0 yoffset=0 xadvance=2 page=1 chnl=15
+kernings count=1
+kerning first=65 second=66 amount=-1
+"#;
+        let font = BitmapFont::from_bmfont(descriptor, vec![Color::WHITE; 8], 4, 2).unwrap();
+
+        assert_eq!(font.line_height(), 3);
+        assert_eq!(font.glyph('A').unwrap().y_offset, 1);
+        assert_eq!(font.glyph('B').unwrap().x, 2);
+        assert!(font.glyph('C').is_none());
+        assert_eq!(font.kerning('A', 'B'), -1);
+        assert_eq!(font.measure("AB"), (4, 3));
+
+        assert!(BitmapFont::from_bmfont(descriptor, vec![Color::WHITE; 4], 2, 2).is_none());
+        assert!(BitmapFont::from_bmfont("char id=65", vec![], 0, 0).is_none());
+    }
+
+    #[test]
+    fn rejects_negative_and_overflowing_glyphs() {
+        let image = || vec![Color::WHITE; 8];
+        let font = |glyph: &str| {
+            let descriptor = format!("common lineHeight=2\nchar {glyph}");
+            BitmapFont::from_bmfont(&descriptor, image(), 4, 2)
+        };
+
+        assert!(font("id=65 x=0 y=0 width=2 height=2").is_some());
+        assert!(font("id=65 x=-1 y=0 width=2 height=2").is_none());
+        assert!(font("id=65 x=0 y=-1 width=2 height=2").is_none());
+        assert!(font("id=65 x=0 y=0 width=-2 height=2").is_none());
+        assert!(font("id=65 x=2147483647 y=0 width=2147483647 height=2").is_none());
+        assert!(font("id=-65 x=0 y=0 width=2 height=2").is_none());
+    }
+}
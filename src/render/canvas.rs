@@ -1,32 +1,71 @@
 use crate::math::buffer::Buffer;
 use crate::render::color::Color;
+use crate::render::damage::{Damage, DamageStats, DirtyRect};
+use crate::render::raster::Raster;
 use crate::render::sketch::{Drawable, Sketch};
+use crate::render::target::CanvasImage;
+use mint::Vector2;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Number of rows processed by one task when rendering in parallel.
+#[cfg(feature = "parallel")]
+pub(crate) const BAND_ROWS: usize = 16;
 
 /// Canvas of a game, containing a buffer of pixels to draw to the window.
-#[derive(Clone)]
+///
+/// Canvases can also be used as off-screen render targets.
+/// Draw them onto another canvas using [Canvas::as_image] or [Sketch::canvas].
+#[derive(Clone, Debug)]
 pub struct Canvas {
     buffer: Buffer,
+    damage: Damage,
 }
 
 impl Canvas {
-    /// Create a new canvas from width and height.
+    /// Create a new canvas from width and height, filled with black pixels like [Canvas::cleanse].\
+    /// Clear it with [Color::TRANSPARENT] to draw translucent off-screen canvases.
     #[inline]
     pub fn new(width: usize, height: usize) -> Self {
+        let mut buffer = Buffer::new(width, height);
+        buffer.fill(Color::BLACK.0);
+
         Self {
-            buffer: Buffer::new(width, height),
+            buffer,
+            damage: Damage::new(height),
         }
     }
 
-    /// Returns the buffer as [Vec] containing 0RGB pixels.
+    /// Returns the buffer as [Vec] containing ARGB pixels.
     #[inline]
     pub fn buffer(self) -> Buffer { self.buffer }
 
-    /// Resize the canvas.
+    /// Returns a reference to the buffer containing ARGB pixels.
+    #[inline]
+    pub fn get_buffer(&self) -> &Buffer { &self.buffer }
+
+    /// Returns a mutable reference to the buffer. Changes are not tracked as dirty regions.
+    #[inline]
+    pub(crate) fn buffer_mut(&mut self) -> &mut Buffer { &mut self.buffer }
+
+    /// Copy the size and pixels of `other` into this canvas, reusing the allocated memory.
+    #[inline]
+    pub fn copy_from(&mut self, other: &Canvas) {
+        self.buffer.buffer.clone_from(&other.buffer.buffer);
+        self.buffer.width = other.width();
+        self.buffer.height = other.height();
+
+        self.damage.rows.resize(other.height(), Default::default());
+        self.damage.mark_all(other.width());
+    }
+
+    /// Resize the canvas. New pixels are black.
     #[inline(never)]
     pub fn resize(&mut self, new_width: usize, new_height: usize) {
         let new_size = new_width * new_height;
 
-        let mut new_buffer: Vec<u32> = vec![0; new_size];
+        let mut new_buffer: Vec<u32> = vec![Color::BLACK.0; new_size];
 
         let copy_width = usize::min(self.width(), new_width);
         let copy_height = usize::min(self.height(), new_height);
@@ -41,6 +80,9 @@ impl Canvas {
         self.buffer.width = new_width;
         self.buffer.height = new_height;
         self.buffer.buffer = new_buffer;
+
+        self.damage.rows.resize(new_height, Default::default());
+        self.damage.mark_all(new_width);
     }
 
     /// Set pixel at `x` and `y` to `color`.
@@ -53,6 +95,33 @@ impl Canvas {
                 // check for index out of bounds
                 if let Some(px) = self.buffer.buffer.get_mut(cx) {
                     *px = color.0;
+                    self.damage.mark(
+                        cx / self.buffer.width,
+                        cx % self.buffer.width,
+                        cx % self.buffer.width + 1,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Blend `color` on top of the pixel at `x` and `y`, respecting the alpha of `color`.
+    #[inline]
+    pub fn blend_pixel(&mut self, x: usize, y: usize, color: &Color) {
+        if color.is_opaque() {
+            self.set_pixel(x, y, color);
+            return;
+        }
+
+        if let Some(cy) = y.checked_mul(self.width()) {
+            if let Some(cx) = cy.checked_add(x) {
+                if let Some(px) = self.buffer.buffer.get_mut(cx) {
+                    *px = Color(*px).blend(*color).0;
+                    self.damage.mark(
+                        cx / self.buffer.width,
+                        cx % self.buffer.width,
+                        cx % self.buffer.width + 1,
+                    );
                 }
             }
         }
@@ -75,30 +144,46 @@ impl Canvas {
     /// **NOTE**: Canvas does not always match window dimensions. To change the background color of the window, use [crate::game::context::GameContext].
     #[inline(never)]
     pub fn clear(&mut self, color: &Color) {
-        for y in 0..self.height() {
-            for x in 0..self.width() {
-                self.set_pixel(x, y, color);
-            }
+        #[cfg(feature = "parallel")]
+        self.par_raster(|raster| raster.fill(color));
+
+        #[cfg(not(feature = "parallel"))]
+        {
+            self.buffer.fill(color.0);
+            self.damage.mark_all(self.width());
+        }
+    }
+
+    /// Fill the rectangle at `x` and `y` with `width` and `height` with `color`, without blending.\
+    /// Useful to erase parts of the canvas when using dirty tracking.
+    #[inline]
+    pub fn clear_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: &Color) {
+        let end_x = x.saturating_add(width).min(self.width());
+        let end_y = y.saturating_add(height).min(self.height());
+
+        for row in y..end_y {
+            self.buffer.fill_span(row, x, end_x, color.0);
+            self.damage.mark(row, x, end_x);
         }
     }
 
     /// Clears the canvas with black pixels.\
     /// Shorthand for [Canvas::clear(&Color::BLACK)].
     #[inline]
-    pub fn cleanse(&mut self) { self.clear(&Color(0)) }
+    pub fn cleanse(&mut self) { self.clear(&Color::BLACK) }
 
     /// Draw a [Sketch] to the canvas.
     #[inline]
-    pub fn draw(&mut self, sketch: &Sketch) {
-        for op in &sketch.0 {
-            op.apply(self);
-        }
-    }
+    pub fn draw(&mut self, sketch: &Sketch) { sketch.apply(self); }
 
     /// Draw a [Drawable] to the canvas.
     #[inline]
     pub fn drawable<T: Drawable>(&mut self, drawable: &T) { drawable.apply(self); }
 
+    /// Get a [CanvasImage] view at `pos`, to draw this canvas onto another one without copying it.
+    #[inline]
+    pub fn as_image(&self, pos: Vector2<u32>) -> CanvasImage<'_> { CanvasImage::new(self, pos) }
+
     /// Get window width.
     #[inline]
     pub fn width(&self) -> usize { self.buffer.width }
@@ -106,4 +191,120 @@ impl Canvas {
     /// Get window height.
     #[inline]
     pub fn height(&self) -> usize { self.buffer.height }
+
+    /// Enable or disable dirty tracking.\
+    /// While enabled, the canvas remembers which regions got drawn to since [Canvas::reset_damage].
+    /// Enabling marks the whole canvas as dirty.
+    #[inline]
+    pub fn set_dirty_tracking(&mut self, enabled: bool) {
+        self.damage.enabled = enabled;
+        self.damage.reset();
+        self.damage.mark_all(self.width());
+    }
+
+    /// Returns `true` if dirty tracking is enabled.
+    #[inline]
+    pub fn is_dirty_tracking(&self) -> bool { self.damage.enabled }
+
+    /// Returns `true` if anything got drawn since the last [Canvas::reset_damage].\
+    /// Always `true` if dirty tracking is disabled.
+    #[inline]
+    pub fn is_dirty(&self) -> bool { !self.damage.enabled || self.damage.is_dirty() }
+
+    /// Returns the regions drawn to since the last [Canvas::reset_damage].\
+    /// Consecutive dirty rows are merged into one rectangle.
+    /// Empty if dirty tracking is disabled.\
+    /// The game uses them to only scale these regions to the window with a logical resolution.
+    #[inline]
+    pub fn dirty_rects(&self) -> Vec<DirtyRect> { self.damage.rects() }
+
+    /// Returns the [DamageStats] of the current frame.
+    #[inline]
+    pub fn damage_stats(&self) -> DamageStats { self.damage.stats() }
+
+    /// Returns the [DamageStats] of the previous frame.
+    #[inline]
+    pub fn previous_damage_stats(&self) -> DamageStats { self.damage.previous }
+
+    /// Forget all dirty regions and start a new frame.\
+    /// Gets called by [crate::game::game::Game] after presenting the canvas.
+    #[inline]
+    pub fn reset_damage(&mut self) { self.damage.reset(); }
+
+    /// Returns the pixels as tightly packed RGB bytes, row by row. Alpha gets dropped.
+    #[inline]
+    pub fn to_rgb_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.buffer.buffer.len() * 3);
+        for px in &self.buffer.buffer {
+            let (r, g, b) = Color(*px).to_rgb();
+            bytes.extend_from_slice(&[r, g, b]);
+        }
+        bytes
+    }
+
+    /// Write the canvas as binary PPM (`P6`) image to `writer`. Alpha gets dropped.
+    #[inline]
+    pub fn write_ppm(&self, mut writer: impl Write) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width(), self.height())?;
+        writer.write_all(&self.to_rgb_bytes())?;
+        writer.flush()
+    }
+
+    /// Save the canvas as binary PPM image to `path`.\
+    /// Works without any features, use [Canvas::save_png] or [Canvas::save_bmp] for common formats.
+    #[inline]
+    pub fn save_ppm(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.write_ppm(BufWriter::new(File::create(path)?))
+    }
+
+    /// Save the canvas as PNG image to `path`.\
+    /// Requires `image` feature.
+    #[cfg(feature = "image")]
+    #[inline]
+    pub fn save_png(&self, path: impl AsRef<Path>) -> image::ImageResult<()> {
+        crate::image::canvas_to_rgb(self).save_with_format(path, image::ImageFormat::Png)
+    }
+
+    /// Save the canvas as BMP image to `path`.\
+    /// Requires `image` feature.
+    #[cfg(feature = "image")]
+    #[inline]
+    pub fn save_bmp(&self, path: impl AsRef<Path>) -> image::ImageResult<()> {
+        crate::image::canvas_to_rgb(self).save_with_format(path, image::ImageFormat::Bmp)
+    }
+
+    /// Get a [Raster] of the whole canvas.
+    #[inline]
+    pub(crate) fn raster(&mut self) -> Raster<'_> {
+        let width = self.width();
+        let damage = self
+            .damage
+            .enabled
+            .then_some(self.damage.rows.as_mut_slice());
+        Raster::new(&mut self.buffer.buffer, width, 0).with_damage(damage)
+    }
+
+    /// Split the canvas into bands of [BAND_ROWS] rows and call `f` on each of them in parallel.
+    #[cfg(feature = "parallel")]
+    #[inline]
+    pub(crate) fn par_raster(&mut self, f: impl Fn(&mut Raster) + Sync) {
+        use rayon::prelude::*;
+
+        let width = self.width();
+        if width == 0 {
+            return;
+        }
+
+        let enabled = self.damage.enabled;
+
+        self.buffer
+            .buffer
+            .par_chunks_mut(width * BAND_ROWS)
+            .zip(self.damage.rows.par_chunks_mut(BAND_ROWS))
+            .enumerate()
+            .for_each(|(band, (pixels, damage))| {
+                let raster = Raster::new(pixels, width, band * BAND_ROWS);
+                f(&mut raster.with_damage(enabled.then_some(damage)))
+            });
+    }
 }
@@ -1,5 +1,11 @@
-/// Contains u32 in 0RGP format, to represent Colors.
-#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialOrd, PartialEq)]
+use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
+
+/// Contains u32 in ARGB format, to represent Colors.
+///
+/// The alpha channel is stored *straight* (not premultiplied) in the highest byte.\
+/// Colors created with [Color::from_rgb] are fully opaque.\
+/// **NOTE**: Raw `0xRRGGBB` values have an alpha of `0` and are invisible, use [Color::from_rgb_u32] for them.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialOrd, PartialEq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color(pub u32);
 
@@ -18,15 +24,18 @@ impl Color {
     pub const ORANGE: Color = Color::from_rgb(255, 165, 0);
     pub const PINK: Color = Color::from_rgb(238, 130, 238);
     pub const TURQUOISE: Color = Color::from_rgb(64, 224, 208);
+    /// Fully transparent black.
+    pub const TRANSPARENT: Color = Color::from_rgba(0, 0, 0, 0);
 
-    /// Create a new Color from RGB values.
+    /// Create a new opaque Color from RGB values.
     #[inline]
-    pub const fn from_rgb(r: u8, g: u8, b: u8) -> Color {
-        let (r, g, b) = (r as u32, g as u32, b as u32);
-        Color((r << 16) | (g << 8) | b)
-    }
+    pub const fn from_rgb(r: u8, g: u8, b: u8) -> Color { Color::from_rgba(r, g, b, 255) }
+
+    /// Create a new opaque Color from a `0xRRGGBB` value, ignoring the highest byte.
+    #[inline]
+    pub const fn from_rgb_u32(rgb: u32) -> Color { Color(0xFF00_0000 | (rgb & 0x00FF_FFFF)) }
 
-    /// Convert Color to RGB values.
+    /// Convert Color to RGB values, ignoring alpha.
     #[inline]
     pub fn to_rgb(self) -> (u8, u8, u8) {
         let r = ((self.0 >> 16) & 0xFF) as u8;
@@ -36,15 +45,537 @@ impl Color {
         (r, g, b)
     }
 
-    /// Create a new Color from RGBA values.
+    /// Create a new Color from straight (not premultiplied) RGBA values.
     #[inline]
     pub const fn from_rgba(r: u8, g: u8, b: u8, a: u8) -> Color {
-        let (r, g, b, a) = (
-            ((r as u32 * a as u32) >> 8),
-            ((g as u32 * a as u32) >> 8),
-            ((b as u32 * a as u32) >> 8),
-            a as u32,
-        );
+        let (r, g, b, a) = (r as u32, g as u32, b as u32, a as u32);
         Color((a << 24) | (r << 16) | (g << 8) | b)
     }
+
+    /// Convert Color to straight RGBA values.
+    #[inline]
+    pub fn to_rgba(self) -> (u8, u8, u8, u8) {
+        let (r, g, b) = self.to_rgb();
+        (r, g, b, self.alpha())
+    }
+
+    /// Create a new Color from premultiplied RGBA values, converting them to straight alpha.
+    #[inline]
+    pub fn from_premultiplied_rgba(r: u8, g: u8, b: u8, a: u8) -> Color {
+        if a == 0 {
+            return Color::TRANSPARENT;
+        }
+
+        let unmultiply = |c: u8| ((c as u32 * 255 + a as u32 / 2) / a as u32).min(255) as u8;
+        Color::from_rgba(unmultiply(r), unmultiply(g), unmultiply(b), a)
+    }
+
+    /// Convert Color to premultiplied RGBA values.
+    #[inline]
+    pub fn to_premultiplied_rgba(self) -> (u8, u8, u8, u8) {
+        let (r, g, b, a) = self.to_rgba();
+        let multiply = |c: u8| mul_u8(c, a);
+        (multiply(r), multiply(g), multiply(b), a)
+    }
+
+    /// Get the red channel.
+    #[inline]
+    pub const fn red(self) -> u8 { (self.0 >> 16) as u8 }
+
+    /// Get the green channel.
+    #[inline]
+    pub const fn green(self) -> u8 { (self.0 >> 8) as u8 }
+
+    /// Get the blue channel.
+    #[inline]
+    pub const fn blue(self) -> u8 { self.0 as u8 }
+
+    /// Get the alpha channel, where `0` is fully transparent and `255` is fully opaque.
+    #[inline]
+    pub const fn alpha(self) -> u8 { (self.0 >> 24) as u8 }
+
+    /// Returns the same color with alpha set to `alpha`.
+    #[inline]
+    pub const fn with_alpha(self, alpha: u8) -> Color {
+        Color((self.0 & 0x00FF_FFFF) | ((alpha as u32) << 24))
+    }
+
+    /// Returns `true` if the alpha channel is `255`.
+    #[inline]
+    pub const fn is_opaque(self) -> bool { self.alpha() == 255 }
+
+    /// Create a new opaque Color from HSV values.\
+    /// `h` is the hue in degrees, `s` and `v` range from `0.0` to `1.0`.
+    #[inline]
+    pub fn from_hsv(h: f32, s: f32, v: f32) -> Color {
+        let s = s.clamp(0.0, 1.0);
+        let v = v.clamp(0.0, 1.0);
+        let c = v * s;
+
+        from_hue_chroma(h, c, v - c)
+    }
+
+    /// Convert Color to HSV values, ignoring alpha.\
+    /// The hue is in degrees from `0.0` to `360.0`, saturation and value from `0.0` to `1.0`.
+    #[inline]
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (h, max, min) = hue_max_min(self);
+        let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+
+        (h, s, max)
+    }
+
+    /// Create a new opaque Color from HSL values.\
+    /// `h` is the hue in degrees, `s` and `l` range from `0.0` to `1.0`.
+    #[inline]
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Color {
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+
+        from_hue_chroma(h, c, l - c / 2.0)
+    }
+
+    /// Convert Color to HSL values, ignoring alpha.\
+    /// The hue is in degrees from `0.0` to `360.0`, saturation and lightness from `0.0` to `1.0`.
+    #[inline]
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let (h, max, min) = hue_max_min(self);
+        let l = (max + min) / 2.0;
+        let s = if max == min {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * l - 1.0).abs())
+        };
+
+        (h, s.clamp(0.0, 1.0), l)
+    }
+
+    /// Linearly interpolate every channel (including alpha) between `self` and `other`.\
+    /// `t` gets clamped to `0.0..=1.0`, where `0.0` returns `self` and `1.0` returns `other`.
+    #[inline]
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let mut out = 0;
+
+        for shift in [0, 8, 16, 24] {
+            let a = ((self.0 >> shift) & 0xFF) as f32;
+            let b = ((other.0 >> shift) & 0xFF) as f32;
+            out |= ((a + (b - a) * t).round() as u32) << shift;
+        }
+
+        Color(out)
+    }
+
+    /// Multiply the brightness (HSV value) by `factor`, keeping hue and alpha.
+    #[inline]
+    pub fn adjust_brightness(self, factor: f32) -> Color {
+        let (h, s, v) = self.to_hsv();
+        Color::from_hsv(h, s, v * factor).with_alpha(self.alpha())
+    }
+
+    /// Multiply the saturation (HSV saturation) by `factor`, keeping hue and alpha.\
+    /// A factor of `0.0` returns a grayscale color.
+    #[inline]
+    pub fn adjust_saturation(self, factor: f32) -> Color {
+        let (h, s, v) = self.to_hsv();
+        Color::from_hsv(h, s * factor, v).with_alpha(self.alpha())
+    }
+
+    /// Parse a color in `#RRGGBB` or `#RRGGBBAA` format. The leading `#` is optional.\
+    /// Returns [None] if the string is not a valid hex color.
+    #[inline]
+    pub fn from_hex(hex: &str) -> Option<Color> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+
+        match hex.len() {
+            6 => Some(Color::from_rgb(channel(0)?, channel(2)?, channel(4)?)),
+            8 => {
+                Some(Color::from_rgba(
+                    channel(0)?,
+                    channel(2)?,
+                    channel(4)?,
+                    channel(6)?,
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the color constant with the case-insensitive `name`, like `"red"` for [Color::RED].\
+    /// Returns [None] if there is no such constant.
+    #[inline]
+    pub fn from_name(name: &str) -> Option<Color> {
+        let color = match name.to_ascii_lowercase().as_str() {
+            "red" => Color::RED,
+            "green" => Color::GREEN,
+            "blue" => Color::BLUE,
+            "yellow" => Color::YELLOW,
+            "cyan" => Color::CYAN,
+            "magenta" => Color::MAGENTA,
+            "black" => Color::BLACK,
+            "white" => Color::WHITE,
+            "gray" => Color::GRAY,
+            "purple" => Color::PURPLE,
+            "aqua" => Color::AQUA,
+            "orange" => Color::ORANGE,
+            "pink" => Color::PINK,
+            "turquoise" => Color::TURQUOISE,
+            "transparent" => Color::TRANSPARENT,
+            _ => return None,
+        };
+        Some(color)
+    }
+
+    /// Convert Color to a hex string in `#RRGGBBAA` format.
+    #[inline]
+    pub fn to_hex(self) -> String {
+        let (r, g, b, a) = self.to_rgba();
+        format!("#{:02X}{:02X}{:02X}{:02X}", r, g, b, a)
+    }
+
+    /// Composite `over` on top of `self` using straight alpha ("source over").
+    #[inline]
+    pub fn blend(self, over: Color) -> Color {
+        let src_a = over.alpha() as u32;
+
+        if src_a == 255 {
+            return over;
+        }
+
+        if src_a == 0 {
+            return self;
+        }
+
+        let inv_a = 255 - src_a;
+        let dst_a = self.alpha() as u32;
+
+        // fast path for the common case of blending onto an opaque pixel
+        if dst_a == 255 {
+            let channel = |shift: u32| {
+                let src = (over.0 >> shift) & 0xFF;
+                let dst = (self.0 >> shift) & 0xFF;
+                (src * src_a + dst * inv_a + 127) / 255
+            };
+
+            return Color(0xFF00_0000 | (channel(16) << 16) | (channel(8) << 8) | channel(0));
+        }
+
+        // alpha of the result, scaled by 255
+        let out_a = src_a * 255 + dst_a * inv_a;
+
+        let channel = |shift: u32| {
+            let src = (over.0 >> shift) & 0xFF;
+            let dst = (self.0 >> shift) & 0xFF;
+            (src * src_a * 255 + dst * dst_a * inv_a + out_a / 2) / out_a
+        };
+
+        Color((((out_a + 127) / 255) << 24) | (channel(16) << 16) | (channel(8) << 8) | channel(0))
+    }
+}
+
+impl Default for Color {
+    /// Opaque [Color::BLACK].
+    #[inline]
+    fn default() -> Self { Color::BLACK }
+}
+
+impl Add for Color {
+    type Output = Color;
+
+    /// Add every channel (including alpha), saturating at `255`.
+    #[inline]
+    fn add(self, rhs: Color) -> Color { map_channels(self, rhs, |a, b| a.saturating_add(b)) }
+}
+
+impl Sub for Color {
+    type Output = Color;
+
+    /// Subtract every channel (including alpha), saturating at `0`.
+    #[inline]
+    fn sub(self, rhs: Color) -> Color { map_channels(self, rhs, |a, b| a.saturating_sub(b)) }
+}
+
+impl Mul for Color {
+    type Output = Color;
+
+    /// Multiply every channel (including alpha), where `255` equals `1.0`. Useful for tinting.
+    #[inline]
+    fn mul(self, rhs: Color) -> Color { map_channels(self, rhs, mul_u8) }
+}
+
+impl Mul<f32> for Color {
+    type Output = Color;
+
+    /// Scale the RGB channels by `rhs`, keeping alpha.
+    #[inline]
+    fn mul(self, rhs: f32) -> Color {
+        let scale = |c: u8| (c as f32 * rhs).round().clamp(0.0, 255.0) as u8;
+        let (r, g, b, a) = self.to_rgba();
+        Color::from_rgba(scale(r), scale(g), scale(b), a)
+    }
+}
+
+impl AddAssign for Color {
+    #[inline]
+    fn add_assign(&mut self, rhs: Color) { *self = *self + rhs; }
+}
+
+impl SubAssign for Color {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Color) { *self = *self - rhs; }
+}
+
+impl MulAssign for Color {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Color) { *self = *self * rhs; }
+}
+
+impl MulAssign<f32> for Color {
+    #[inline]
+    fn mul_assign(&mut self, rhs: f32) { *self = *self * rhs; }
+}
+
+/// Multiply two bytes, where `255` equals `1.0`.
+#[inline]
+fn mul_u8(a: u8, b: u8) -> u8 {
+    let x = a as u32 * b as u32 + 128;
+    ((x + (x >> 8)) >> 8) as u8
+}
+
+/// Apply `f` to every channel of `a` and `b`.
+#[inline]
+fn map_channels(a: Color, b: Color, f: impl Fn(u8, u8) -> u8) -> Color {
+    let mut out = 0;
+    for shift in [0, 8, 16, 24] {
+        out |= (f((a.0 >> shift) as u8, (b.0 >> shift) as u8) as u32) << shift;
+    }
+    Color(out)
+}
+
+/// Get hue in degrees, max and min channel of a color in `0.0..=1.0`.
+#[inline]
+fn hue_max_min(color: Color) -> (f32, f32, f32) {
+    let (r, g, b) = color.to_rgb();
+    let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let h = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+
+    (h, max, min)
+}
+
+/// Build an opaque color from hue in degrees, chroma and the amount to add to each channel.
+#[inline]
+fn from_hue_chroma(h: f32, c: f32, m: f32) -> Color {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+
+    let to_u8 = |v: f32| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    Color::from_rgb(to_u8(r), to_u8(g), to_u8(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgb_is_opaque() {
+        let color = Color::from_rgb(10, 20, 30);
+        assert_eq!(color.to_rgb(), (10, 20, 30));
+        assert_eq!(color.alpha(), 255);
+        assert!(color.is_opaque());
+    }
+
+    #[test]
+    fn rgb_u32_is_opaque() {
+        assert_eq!(
+            Color::from_rgb_u32(0x10_20_30),
+            Color::from_rgb(0x10, 0x20, 0x30)
+        );
+        assert_eq!(Color::from_rgb_u32(0x7F_FF_A5_00), Color::ORANGE);
+        assert_eq!(Color::default(), Color::BLACK);
+    }
+
+    #[test]
+    fn rgba_round_trip() {
+        let color = Color::from_rgba(10, 20, 30, 40);
+        assert_eq!(color.to_rgba(), (10, 20, 30, 40));
+        assert_eq!(color.to_rgb(), (10, 20, 30));
+        assert_eq!(
+            (color.red(), color.green(), color.blue(), color.alpha()),
+            (10, 20, 30, 40)
+        );
+    }
+
+    #[test]
+    fn with_alpha_keeps_rgb() {
+        let color = Color::ORANGE.with_alpha(128);
+        assert_eq!(color.to_rgba(), (255, 165, 0, 128));
+        assert_eq!(color.with_alpha(255), Color::ORANGE);
+    }
+
+    #[test]
+    fn premultiplied_round_trip() {
+        let color = Color::from_rgba(200, 100, 50, 128);
+        let (r, g, b, a) = color.to_premultiplied_rgba();
+        assert_eq!((r, g, b, a), (100, 50, 25, 128));
+
+        let back = Color::from_premultiplied_rgba(r, g, b, a);
+        assert_eq!(back.alpha(), 128);
+        assert!((back.red() as i32 - 200).abs() <= 1);
+        assert!((back.green() as i32 - 100).abs() <= 1);
+        assert!((back.blue() as i32 - 50).abs() <= 1);
+
+        assert_eq!(
+            Color::from_premultiplied_rgba(10, 10, 10, 0),
+            Color::TRANSPARENT
+        );
+    }
+
+    #[test]
+    fn hsv_conversion() {
+        assert_eq!(Color::from_hsv(0.0, 1.0, 1.0), Color::RED);
+        assert_eq!(Color::from_hsv(120.0, 1.0, 1.0), Color::GREEN);
+        assert_eq!(Color::from_hsv(240.0, 1.0, 1.0), Color::BLUE);
+        assert_eq!(Color::from_hsv(360.0, 1.0, 1.0), Color::RED);
+        assert_eq!(Color::from_hsv(0.0, 0.0, 1.0), Color::WHITE);
+
+        let (h, s, v) = Color::YELLOW.to_hsv();
+        assert_eq!((h, s, v), (60.0, 1.0, 1.0));
+
+        let (h, s, v) = Color::BLACK.to_hsv();
+        assert_eq!((h, s, v), (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn hsl_conversion() {
+        assert_eq!(Color::from_hsl(0.0, 1.0, 0.5), Color::RED);
+        assert_eq!(Color::from_hsl(180.0, 1.0, 0.5), Color::CYAN);
+        assert_eq!(Color::from_hsl(0.0, 0.0, 1.0), Color::WHITE);
+
+        let (h, s, l) = Color::MAGENTA.to_hsl();
+        assert_eq!((h, s, l), (300.0, 1.0, 0.5));
+
+        let (_, s, l) = Color::GRAY.to_hsl();
+        assert_eq!(s, 0.0);
+        assert!((l - 128.0 / 255.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn hsv_round_trip() {
+        for color in [Color::ORANGE, Color::PINK, Color::TURQUOISE, Color::PURPLE] {
+            let (h, s, v) = color.to_hsv();
+            assert_eq!(Color::from_hsv(h, s, v), color);
+
+            let (h, s, l) = color.to_hsl();
+            assert_eq!(Color::from_hsl(h, s, l), color);
+        }
+    }
+
+    #[test]
+    fn lerp() {
+        let from = Color::from_rgba(0, 0, 0, 0);
+        let to = Color::from_rgba(255, 100, 50, 255);
+
+        assert_eq!(from.lerp(to, 0.0), from);
+        assert_eq!(from.lerp(to, 1.0), to);
+        assert_eq!(from.lerp(to, 0.5), Color::from_rgba(128, 50, 25, 128));
+        assert_eq!(from.lerp(to, 2.0), to);
+    }
+
+    #[test]
+    fn brightness_and_saturation() {
+        assert_eq!(Color::WHITE.adjust_brightness(0.0), Color::BLACK);
+        assert_eq!(
+            Color::RED.adjust_brightness(0.5),
+            Color::from_rgb(128, 0, 0)
+        );
+        assert_eq!(Color::RED.adjust_saturation(0.0), Color::WHITE);
+        assert_eq!(Color::RED.with_alpha(10).adjust_brightness(0.5).alpha(), 10);
+    }
+
+    #[test]
+    fn hex_parsing() {
+        assert_eq!(Color::from_hex("#FF0000"), Some(Color::RED));
+        assert_eq!(Color::from_hex("00ff00"), Some(Color::GREEN));
+        assert_eq!(
+            Color::from_hex("#0000FF80"),
+            Some(Color::BLUE.with_alpha(0x80))
+        );
+        assert_eq!(Color::from_hex("#FFF"), None);
+        assert_eq!(Color::from_hex("#GG0000"), None);
+        assert_eq!(Color::from_hex("#+F0000"), None);
+        assert_eq!(Color::from_hex("#ÄÄÄ"), None);
+
+        assert_eq!(Color::TURQUOISE.to_hex(), "#40E0D0FF");
+        assert_eq!(Color::from_hex(&Color::PINK.to_hex()), Some(Color::PINK));
+    }
+
+    #[test]
+    fn name_parsing() {
+        assert_eq!(Color::from_name("Orange"), Some(Color::ORANGE));
+        assert_eq!(Color::from_name("transparent"), Some(Color::TRANSPARENT));
+        assert_eq!(Color::from_name("#FF0000"), None);
+    }
+
+    #[test]
+    fn operators() {
+        let a = Color::from_rgba(200, 100, 0, 255);
+        let b = Color::from_rgba(100, 100, 100, 0);
+
+        assert_eq!(a + b, Color::from_rgba(255, 200, 100, 255));
+        assert_eq!(a - b, Color::from_rgba(100, 0, 0, 255));
+        assert_eq!(Color::WHITE * Color::ORANGE, Color::ORANGE);
+        assert_eq!(Color::ORANGE * Color::BLACK, Color::BLACK);
+        assert_eq!(a * 0.5, Color::from_rgba(100, 50, 0, 255));
+        assert_eq!(a * 2.0, Color::from_rgba(255, 200, 0, 255));
+
+        let mut c = a;
+        c += b;
+        c -= b;
+        c *= 1.0;
+        c *= Color::WHITE;
+        assert_eq!(c, Color::from_rgba(155, 100, 0, 255));
+    }
+
+    #[test]
+    fn blend() {
+        assert_eq!(Color::RED.blend(Color::BLUE), Color::BLUE);
+        assert_eq!(Color::RED.blend(Color::TRANSPARENT), Color::RED);
+        assert_eq!(
+            Color::BLACK.blend(Color::WHITE.with_alpha(128)),
+            Color::from_rgb(128, 128, 128)
+        );
+        assert_eq!(
+            Color::TRANSPARENT.blend(Color::RED.with_alpha(128)),
+            Color::RED.with_alpha(128)
+        );
+    }
 }
@@ -0,0 +1,232 @@
+/// Range of columns of a single row, which got drawn to in the current frame.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RowDamage {
+    start: usize,
+    end: usize,
+    touched: usize,
+}
+
+impl Default for RowDamage {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            start: usize::MAX,
+            end: 0,
+            touched: 0,
+        }
+    }
+}
+
+impl RowDamage {
+    /// Mark the columns from `start` to `end` (exclusive) as drawn.
+    #[inline]
+    pub(crate) fn mark(&mut self, start: usize, end: usize) {
+        if start < end {
+            self.start = self.start.min(start);
+            self.end = self.end.max(end);
+            self.touched += end - start;
+        }
+    }
+
+    /// Returns `true` if any pixel of the row got drawn.
+    #[inline]
+    pub(crate) fn is_dirty(&self) -> bool { self.start < self.end }
+}
+
+/// A rectangular region of the canvas, which got drawn to.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DirtyRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Statistics about the pixels drawn in a frame with dirty tracking enabled.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DamageStats {
+    /// How many pixel writes happened. Pixels drawn multiple times are counted multiple times.
+    pub pixels_touched: usize,
+    /// How many pixels are inside of the dirty regions and need to be presented.
+    pub dirty_pixels: usize,
+    /// How many rows contain dirty pixels.
+    pub dirty_rows: usize,
+}
+
+/// Dirty region tracking of a canvas.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Damage {
+    pub(crate) enabled: bool,
+    pub(crate) rows: Vec<RowDamage>,
+    pub(crate) previous: DamageStats,
+}
+
+impl Damage {
+    /// Create a new disabled tracker for `height` rows.
+    #[inline]
+    pub(crate) fn new(height: usize) -> Self {
+        Self {
+            enabled: false,
+            rows: vec![RowDamage::default(); height],
+            previous: DamageStats::default(),
+        }
+    }
+
+    /// Mark columns `start` to `end` (exclusive) of row `y` as drawn, if tracking is enabled.
+    #[inline]
+    pub(crate) fn mark(&mut self, y: usize, start: usize, end: usize) {
+        if self.enabled {
+            if let Some(row) = self.rows.get_mut(y) {
+                row.mark(start, end);
+            }
+        }
+    }
+
+    /// Mark every row as fully drawn, if tracking is enabled.
+    #[inline]
+    pub(crate) fn mark_all(&mut self, width: usize) {
+        if self.enabled {
+            self.rows.iter_mut().for_each(|row| row.mark(0, width));
+        }
+    }
+
+    /// Returns `true` if anything got drawn.
+    #[inline]
+    pub(crate) fn is_dirty(&self) -> bool { self.rows.iter().any(RowDamage::is_dirty) }
+
+    /// Calculate the statistics of the current frame.
+    #[inline]
+    pub(crate) fn stats(&self) -> DamageStats {
+        self.rows
+            .iter()
+            .filter(|row| row.is_dirty())
+            .fold(DamageStats::default(), |stats, row| {
+                DamageStats {
+                    pixels_touched: stats.pixels_touched + row.touched,
+                    dirty_pixels: stats.dirty_pixels + row.end - row.start,
+                    dirty_rows: stats.dirty_rows + 1,
+                }
+            })
+    }
+
+    /// Merge consecutive dirty rows into rectangles.
+    #[inline]
+    pub(crate) fn rects(&self) -> Vec<DirtyRect> {
+        let mut rects: Vec<DirtyRect> = Vec::new();
+        let mut current: Option<DirtyRect> = None;
+
+        for (y, row) in self.rows.iter().enumerate() {
+            if !row.is_dirty() {
+                rects.extend(current.take());
+                continue;
+            }
+
+            current = Some(match current {
+                Some(rect) => {
+                    let x = rect.x.min(row.start);
+                    let end = (rect.x + rect.width).max(row.end);
+                    DirtyRect {
+                        x,
+                        y: rect.y,
+                        width: end - x,
+                        height: rect.height + 1,
+                    }
+                }
+                None => {
+                    DirtyRect {
+                        x: row.start,
+                        y,
+                        width: row.end - row.start,
+                        height: 1,
+                    }
+                }
+            });
+        }
+
+        rects.extend(current);
+        rects
+    }
+
+    /// Store the statistics of the current frame and start a new frame.
+    #[inline]
+    pub(crate) fn reset(&mut self) {
+        self.previous = self.stats();
+        self.rows.fill(RowDamage::default());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::canvas::Canvas;
+    use crate::render::color::Color;
+    use crate::render::damage::{DamageStats, DirtyRect};
+    use crate::render::sketch::Sketch;
+    use crate::simplified::vec2;
+
+    #[test]
+    fn disabled_tracking_is_always_dirty() {
+        let mut canvas = Canvas::new(64, 48);
+        canvas.draw(Sketch::new().rect(vec2(4, 4), 8, 8, Color::RED));
+
+        assert!(canvas.is_dirty());
+        assert!(canvas.dirty_rects().is_empty());
+    }
+
+    #[test]
+    fn tracks_drawn_regions() {
+        let mut canvas = Canvas::new(64, 48);
+        canvas.set_dirty_tracking(true);
+        canvas.reset_damage();
+        assert!(!canvas.is_dirty());
+
+        canvas.draw(
+            Sketch::new()
+                .rect(vec2(4, 4), 8, 8, Color::RED)
+                .rect(vec2(6, 6), 8, 2, Color::BLUE)
+                .rect(vec2(30, 40), 100, 100, Color::GREEN),
+        );
+
+        assert!(canvas.is_dirty());
+        assert_eq!(
+            canvas.dirty_rects(),
+            vec![
+                DirtyRect {
+                    x: 4,
+                    y: 4,
+                    width: 10,
+                    height: 8,
+                },
+                DirtyRect {
+                    x: 30,
+                    y: 40,
+                    width: 34,
+                    height: 8,
+                },
+            ]
+        );
+        assert_eq!(
+            canvas.damage_stats(),
+            DamageStats {
+                pixels_touched: 8 * 8 + 8 * 2 + 34 * 8,
+                dirty_pixels: 8 * 6 + 10 * 2 + 34 * 8,
+                dirty_rows: 16,
+            }
+        );
+    }
+
+    #[test]
+    fn reset_keeps_previous_stats() {
+        let mut canvas = Canvas::new(64, 48);
+        canvas.set_dirty_tracking(true);
+        canvas.reset_damage();
+
+        canvas.set_pixel(3, 5, &Color::WHITE);
+        canvas.clear_rect(10, 10, 2, 2, &Color::BLACK);
+        let stats = canvas.damage_stats();
+        assert_eq!(stats.pixels_touched, 5);
+
+        canvas.reset_damage();
+        assert_eq!(canvas.previous_damage_stats(), stats);
+        assert_eq!(canvas.damage_stats(), DamageStats::default());
+    }
+}
@@ -0,0 +1,289 @@
+use crate::render::color::Color;
+use mint::Vector2;
+
+/// How to fill the area of a shape, like a rectangle, circle, oval or polygon.
+///
+/// Every [Color] converts into a [Fill::Solid], so shape functions accept both.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub enum Fill {
+    /// Fill with a single color.
+    Solid(Color),
+    /// Fill with a [LinearGradient].
+    Linear(LinearGradient),
+    /// Fill with a [RadialGradient].
+    Radial(RadialGradient),
+}
+
+impl Fill {
+    /// Get the color of the fill at canvas position `x` and `y`.
+    #[inline]
+    pub fn color_at(&self, x: f32, y: f32) -> Color {
+        match self {
+            Fill::Solid(color) => *color,
+            Fill::Linear(gradient) => gradient.color_at(x, y),
+            Fill::Radial(gradient) => gradient.color_at(x, y),
+        }
+    }
+
+    /// Move the gradient of the fill by `dx` and `dy`.
+    #[inline]
+    pub(crate) fn translate(&mut self, dx: i32, dy: i32) {
+        match self {
+            Fill::Solid(_) => {}
+            Fill::Linear(gradient) => {
+                shift(&mut gradient.start, dx, dy);
+                shift(&mut gradient.end, dx, dy);
+            }
+            Fill::Radial(gradient) => shift(&mut gradient.center, dx, dy),
+        }
+    }
+}
+
+impl Default for Fill {
+    #[inline]
+    fn default() -> Self { Self::Solid(Color::default()) }
+}
+
+impl From<Color> for Fill {
+    #[inline]
+    fn from(color: Color) -> Self { Self::Solid(color) }
+}
+
+impl From<LinearGradient> for Fill {
+    #[inline]
+    fn from(gradient: LinearGradient) -> Self { Self::Linear(gradient) }
+}
+
+impl From<RadialGradient> for Fill {
+    #[inline]
+    fn from(gradient: RadialGradient) -> Self { Self::Radial(gradient) }
+}
+
+/// A color at a specific offset of a gradient.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct ColorStop {
+    /// Offset of the stop, where `0.0` is the start and `1.0` is the end of the gradient.
+    pub offset: f32,
+    /// Color of the stop.
+    pub color: Color,
+}
+
+/// A gradient along the line from `start` to `end` in canvas coordinates.\
+/// Pixels before `start` or after `end` use the color of the first or last stop.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct LinearGradient {
+    start: Vector2<u32>,
+    end: Vector2<u32>,
+    stops: Vec<ColorStop>,
+}
+
+impl LinearGradient {
+    /// Create a new linear gradient without any color stops.
+    #[inline]
+    pub fn new(start: Vector2<u32>, end: Vector2<u32>) -> Self {
+        Self {
+            start,
+            end,
+            stops: Vec::new(),
+        }
+    }
+
+    /// Add a color stop at `offset` (from `0.0` to `1.0`).
+    #[inline]
+    pub fn with_stop(mut self, offset: f32, color: Color) -> Self {
+        insert_stop(&mut self.stops, offset, color);
+        self
+    }
+
+    /// Returns the color stops sorted by offset.
+    #[inline]
+    pub fn stops(&self) -> &[ColorStop] { &self.stops }
+
+    /// Get the color of the gradient at canvas position `x` and `y`.
+    #[inline]
+    pub fn color_at(&self, x: f32, y: f32) -> Color {
+        let (sx, sy) = (self.start.x as f32, self.start.y as f32);
+        let (dx, dy) = (self.end.x as f32 - sx, self.end.y as f32 - sy);
+        let length_squared = dx * dx + dy * dy;
+
+        let t = if length_squared == 0.0 {
+            0.0
+        } else {
+            ((x - sx) * dx + (y - sy) * dy) / length_squared
+        };
+
+        sample_stops(&self.stops, t)
+    }
+}
+
+/// A gradient spreading in a circle from `center` to `radius` in canvas coordinates.\
+/// Pixels outside the radius use the color of the last stop.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct RadialGradient {
+    center: Vector2<u32>,
+    radius: u32,
+    stops: Vec<ColorStop>,
+}
+
+impl RadialGradient {
+    /// Create a new radial gradient without any color stops.
+    #[inline]
+    pub fn new(center: Vector2<u32>, radius: u32) -> Self {
+        Self {
+            center,
+            radius,
+            stops: Vec::new(),
+        }
+    }
+
+    /// Add a color stop at `offset` (from `0.0` to `1.0`).
+    #[inline]
+    pub fn with_stop(mut self, offset: f32, color: Color) -> Self {
+        insert_stop(&mut self.stops, offset, color);
+        self
+    }
+
+    /// Returns the color stops sorted by offset.
+    #[inline]
+    pub fn stops(&self) -> &[ColorStop] { &self.stops }
+
+    /// Get the color of the gradient at canvas position `x` and `y`.
+    #[inline]
+    pub fn color_at(&self, x: f32, y: f32) -> Color {
+        let dx = x - self.center.x as f32;
+        let dy = y - self.center.y as f32;
+
+        let t = if self.radius == 0 {
+            1.0
+        } else {
+            (dx * dx + dy * dy).sqrt() / self.radius as f32
+        };
+
+        sample_stops(&self.stops, t)
+    }
+}
+
+/// Move `pos` by `dx` and `dy`, saturating at the bounds of [u32].
+#[inline]
+pub(crate) fn shift(pos: &mut Vector2<u32>, dx: i32, dy: i32) {
+    pos.x = pos.x.saturating_add_signed(dx);
+    pos.y = pos.y.saturating_add_signed(dy);
+}
+
+/// Insert a stop, keeping `stops` sorted by offset.
+#[inline]
+fn insert_stop(stops: &mut Vec<ColorStop>, offset: f32, color: Color) {
+    let offset = offset.clamp(0.0, 1.0);
+    let index = stops.partition_point(|stop| stop.offset <= offset);
+    stops.insert(index, ColorStop { offset, color });
+}
+
+/// Get the interpolated color at `t` of the sorted `stops` or [Color::TRANSPARENT] if there are none.
+#[inline]
+fn sample_stops(stops: &[ColorStop], t: f32) -> Color {
+    let (first, last) = match (stops.first(), stops.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Color::TRANSPARENT,
+    };
+
+    if t <= first.offset {
+        return first.color;
+    }
+
+    if t >= last.offset {
+        return last.color;
+    }
+
+    let index = stops.partition_point(|stop| stop.offset <= t);
+    let (from, to) = (&stops[index - 1], &stops[index]);
+    let range = to.offset - from.offset;
+
+    if range <= 0.0 {
+        return to.color;
+    }
+
+    from.color.lerp(to.color, (t - from.offset) / range)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::color::Color;
+    use crate::render::fill::{Fill, LinearGradient, RadialGradient};
+    use crate::simplified::vec2;
+
+    fn horizontal() -> LinearGradient { LinearGradient::new(vec2(0, 0), vec2(100, 0)) }
+
+    #[test]
+    fn samples_stops_in_order() {
+        let gradient = horizontal()
+            .with_stop(0.75, Color::BLUE)
+            .with_stop(0.25, Color::RED);
+
+        assert_eq!(gradient.stops()[0].color, Color::RED);
+        assert_eq!(gradient.color_at(0.0, 0.0), Color::RED);
+        assert_eq!(gradient.color_at(25.0, 0.0), Color::RED);
+        assert_eq!(gradient.color_at(50.0, 0.0), Color::from_rgb(128, 0, 128));
+        assert_eq!(gradient.color_at(75.0, 0.0), Color::BLUE);
+        assert_eq!(gradient.color_at(150.0, 0.0), Color::BLUE);
+        assert_eq!(gradient.color_at(-50.0, 0.0), Color::RED);
+    }
+
+    #[test]
+    fn duplicate_offsets_make_hard_edges() {
+        let gradient = horizontal()
+            .with_stop(0.0, Color::BLACK)
+            .with_stop(0.5, Color::RED)
+            .with_stop(0.5, Color::BLUE)
+            .with_stop(1.0, Color::BLUE);
+
+        assert_eq!(gradient.color_at(49.0, 0.0), Color::from_rgb(250, 0, 0));
+        assert_eq!(gradient.color_at(50.0, 0.0), Color::BLUE);
+        assert_eq!(gradient.color_at(51.0, 0.0), Color::BLUE);
+    }
+
+    #[test]
+    fn degenerate_gradients() {
+        assert_eq!(horizontal().color_at(50.0, 0.0), Color::TRANSPARENT);
+
+        let point = LinearGradient::new(vec2(5, 5), vec2(5, 5))
+            .with_stop(0.0, Color::RED)
+            .with_stop(1.0, Color::BLUE);
+        assert_eq!(point.color_at(100.0, 0.0), Color::RED);
+
+        let radial = RadialGradient::new(vec2(10, 10), 0)
+            .with_stop(0.0, Color::RED)
+            .with_stop(1.0, Color::BLUE);
+        assert_eq!(radial.color_at(10.0, 10.0), Color::BLUE);
+        assert_eq!(radial.color_at(0.0, 0.0), Color::BLUE);
+    }
+
+    #[test]
+    fn radial_spreads_from_center() {
+        let gradient = RadialGradient::new(vec2(10, 10), 10)
+            .with_stop(0.0, Color::WHITE)
+            .with_stop(1.0, Color::BLACK);
+        let fill = Fill::from(gradient);
+
+        assert_eq!(fill.color_at(10.0, 10.0), Color::WHITE);
+        assert_eq!(fill.color_at(10.0, 15.0), Color::GRAY);
+        assert_eq!(fill.color_at(30.0, 30.0), Color::BLACK);
+    }
+
+    #[test]
+    fn translates_gradients_saturating() {
+        let mut fill = Fill::from(
+            horizontal()
+                .with_stop(0.0, Color::RED)
+                .with_stop(1.0, Color::BLUE),
+        );
+        // the start saturates at 0, while the end moves to 90
+        fill.translate(-10, 5);
+        assert_eq!(fill.color_at(0.0, 0.0), Color::RED);
+        assert_eq!(fill.color_at(45.0, 0.0), Color::from_rgb(128, 0, 128));
+        assert_eq!(fill.color_at(90.0, 0.0), Color::BLUE);
+
+        let mut solid = Fill::from(Color::RED);
+        solid.translate(3, 3);
+        assert_eq!(solid, Fill::Solid(Color::RED));
+    }
+}
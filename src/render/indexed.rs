@@ -0,0 +1,310 @@
+use crate::render::canvas::Canvas;
+use crate::render::color::Color;
+use crate::render::sketch::Drawable;
+use mint::Vector2;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A list of up to 256 colors, which are looked up by the indices of an [IndexedImage].
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Palette {
+    colors: Vec<Color>,
+}
+
+impl Palette {
+    /// Create a new palette from `colors`. Only the first 256 colors can be indexed.
+    #[inline]
+    pub fn new(colors: Vec<Color>) -> Self { Self { colors } }
+
+    /// Returns all colors of the palette.
+    #[inline]
+    pub fn colors(&self) -> &[Color] { &self.colors }
+
+    /// Get the color at `index` or [Color::TRANSPARENT] if the palette has no such color.
+    #[inline]
+    pub fn get(&self, index: u8) -> Color {
+        self.colors
+            .get(index as usize)
+            .copied()
+            .unwrap_or(Color::TRANSPARENT)
+    }
+
+    /// Replace the color at `index`.\
+    /// Returns [None] if the palette has no such color.
+    #[inline]
+    pub fn set(&mut self, index: u8, color: Color) -> Option<()> {
+        *self.colors.get_mut(index as usize)? = color;
+        Some(())
+    }
+
+    /// Rotate the colors in `range` by `steps`. Positive steps move colors to higher indices.\
+    /// Returns [None] if the range is outside of the palette.
+    #[inline]
+    pub fn cycle(&mut self, range: Range<usize>, steps: i32) -> Option<()> {
+        let colors = self.colors.get_mut(range)?;
+        if !colors.is_empty() {
+            let steps = steps.rem_euclid(colors.len() as i32) as usize;
+            colors.rotate_right(steps);
+        }
+        Some(())
+    }
+
+    /// Returns the number of colors.
+    #[inline]
+    pub fn len(&self) -> usize { self.colors.len() }
+
+    /// Returns `true` if the palette has no colors.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.colors.is_empty() }
+}
+
+impl From<Vec<Color>> for Palette {
+    #[inline]
+    fn from(colors: Vec<Color>) -> Self { Self::new(colors) }
+}
+
+/// Palette cycling animation, which rotates a range of palette colors in a fixed interval.
+///
+/// Classic effect to animate water, fire or lights without changing any pixels.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PaletteCycle {
+    range: Range<usize>,
+    interval: Duration,
+    reverse: bool,
+    elapsed: Duration,
+}
+
+impl PaletteCycle {
+    /// Rotate the colors in `range` by one index every `interval`.
+    #[inline]
+    pub fn new(range: Range<usize>, interval: Duration) -> Self {
+        Self {
+            range,
+            interval,
+            reverse: false,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Rotate the colors towards lower indices instead.
+    #[inline]
+    pub fn with_reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+
+    /// Advance the animation by `delta` and return by how many steps the colors rotate.
+    #[inline]
+    pub fn advance(&mut self, delta: Duration) -> i32 {
+        if self.interval.is_zero() {
+            return 0;
+        }
+
+        self.elapsed += delta;
+        let mut steps = 0;
+        while self.elapsed >= self.interval {
+            self.elapsed -= self.interval;
+            steps += 1;
+        }
+
+        if self.reverse {
+            -steps
+        } else {
+            steps
+        }
+    }
+
+    /// Advance the animation by `delta` and rotate `palette` by the number of passed intervals.
+    #[inline]
+    pub fn update(&mut self, palette: &mut Palette, delta: Duration) {
+        let steps = self.advance(delta);
+        if steps != 0 {
+            palette.cycle(self.range.clone(), steps);
+        }
+    }
+}
+
+/// Image storing one palette index per pixel, which gets drawn through a selectable [Palette].
+///
+/// Swap palettes to recolor the same image, e.g. for enemy variants, and add
+/// [PaletteCycle]s to animate its colors. The pixel data is shared between clones.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexedImage {
+    pos: Vector2<u32>,
+    width: u32,
+    height: u32,
+    indices: Arc<Vec<u8>>,
+    palettes: Vec<Palette>,
+    active: usize,
+    cycles: Vec<PaletteCycle>,
+}
+
+impl IndexedImage {
+    /// Create a new image at `pos` with `indices` of size `width` x `height`, drawn through `palette`.\
+    /// Returns [None] if the number of indices doesn't match the size.
+    #[inline]
+    pub fn new(
+        pos: Vector2<u32>,
+        width: u32,
+        height: u32,
+        indices: impl Into<Arc<Vec<u8>>>,
+        palette: impl Into<Palette>,
+    ) -> Option<Self> {
+        let indices = indices.into();
+        if indices.len() != width as usize * height as usize {
+            return None;
+        }
+
+        Some(Self {
+            pos,
+            width,
+            height,
+            indices,
+            palettes: vec![palette.into()],
+            active: 0,
+            cycles: Vec::new(),
+        })
+    }
+
+    /// Add another palette, which can be selected using [IndexedImage::set_palette].
+    #[inline]
+    pub fn with_palette(mut self, palette: impl Into<Palette>) -> Self {
+        self.palettes.push(palette.into());
+        self
+    }
+
+    /// Add a [PaletteCycle], which gets applied to all palettes in [IndexedImage::update].
+    #[inline]
+    pub fn with_cycle(mut self, cycle: PaletteCycle) -> Self {
+        self.cycles.push(cycle);
+        self
+    }
+
+    /// Set the position to draw the image at.
+    #[inline]
+    pub fn set_pos(&mut self, pos: Vector2<u32>) { self.pos = pos; }
+
+    /// Select the palette at `index` to draw the image with.\
+    /// Returns [None] if the palette does not exist.
+    #[inline]
+    pub fn set_palette(&mut self, index: usize) -> Option<()> {
+        if index >= self.palettes.len() {
+            return None;
+        }
+        self.active = index;
+        Some(())
+    }
+
+    /// Get the selected palette.
+    #[inline]
+    pub fn palette(&self) -> &Palette { &self.palettes[self.active] }
+
+    /// Get the selected palette as mutable.
+    #[inline]
+    pub fn palette_mut(&mut self) -> &mut Palette { &mut self.palettes[self.active] }
+
+    /// Returns all palettes.
+    #[inline]
+    pub fn palettes(&self) -> &[Palette] { &self.palettes }
+
+    /// Returns the palette index of every pixel, row by row.
+    #[inline]
+    pub fn indices(&self) -> &[u8] { &self.indices }
+
+    /// Get the width of the image.
+    #[inline]
+    pub fn width(&self) -> u32 { self.width }
+
+    /// Get the height of the image.
+    #[inline]
+    pub fn height(&self) -> u32 { self.height }
+
+    /// Advance all palette cycles by `delta`.
+    #[inline]
+    pub fn update(&mut self, delta: Duration) {
+        for cycle in &mut self.cycles {
+            let steps = cycle.advance(delta);
+            if steps != 0 {
+                for palette in &mut self.palettes {
+                    palette.cycle(cycle.range.clone(), steps);
+                }
+            }
+        }
+    }
+}
+
+impl Drawable for IndexedImage {
+    /// Draw the image through the selected palette.
+    #[inline]
+    fn apply(&self, canvas: &mut Canvas) {
+        let palette = self.palette();
+        let (start_x, start_y) = (self.pos.x as usize, self.pos.y as usize);
+        let width = self.width as usize;
+
+        let mut raster = canvas.raster();
+        let mut row = Vec::with_capacity(width);
+
+        for y in raster.clip_rows(start_y, start_y + self.height as usize) {
+            let offset = (y - start_y) * width;
+
+            row.clear();
+            row.extend(
+                self.indices[offset..offset + width]
+                    .iter()
+                    .map(|index| palette.get(*index)),
+            );
+
+            raster.blend_span(y, start_x, &row);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::canvas::Canvas;
+    use crate::render::color::Color;
+    use crate::render::indexed::{IndexedImage, Palette, PaletteCycle};
+    use crate::simplified::vec2;
+    use std::time::Duration;
+
+    #[test]
+    fn cycles_palette_colors() {
+        let mut palette = Palette::new(vec![Color::BLACK, Color::RED, Color::GREEN, Color::BLUE]);
+        let mut cycle = PaletteCycle::new(1..4, Duration::from_millis(100));
+
+        cycle.update(&mut palette, Duration::from_millis(50));
+        assert_eq!(palette.get(1), Color::RED);
+
+        cycle.update(&mut palette, Duration::from_millis(160));
+        assert_eq!(
+            palette.colors(),
+            &[Color::BLACK, Color::GREEN, Color::BLUE, Color::RED]
+        );
+        assert_eq!(palette.cycle(2..8, 1), None);
+    }
+
+    #[test]
+    fn draws_through_selected_palette() {
+        let mut image = IndexedImage::new(
+            vec2(1, 1),
+            2,
+            1,
+            vec![0, 1],
+            vec![Color::RED, Color::TRANSPARENT],
+        )
+        .unwrap()
+        .with_palette(vec![Color::BLUE, Color::GREEN]);
+        let mut canvas = Canvas::new(4, 3);
+
+        canvas.drawable(&image);
+        assert_eq!(canvas.get_pixel(1, 1), Some(Color::RED));
+        assert_eq!(canvas.get_pixel(2, 1), Some(Color::BLACK));
+
+        image.set_palette(1).unwrap();
+        canvas.drawable(&image);
+        assert_eq!(canvas.get_pixel(1, 1), Some(Color::BLUE));
+        assert_eq!(canvas.get_pixel(2, 1), Some(Color::GREEN));
+        assert_eq!(image.set_palette(2), None);
+    }
+}
@@ -0,0 +1,482 @@
+use crate::render::canvas::Canvas;
+use crate::render::color::Color;
+use crate::render::raster::Raster;
+use crate::render::sketch::Drawable;
+use mint::Vector2;
+
+/// Area lit by a [Light].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LightShape {
+    /// Light in every direction.
+    #[default]
+    Point,
+    /// Light towards `direction` in radians, spreading `angle` radians in total.
+    Cone { direction: f32, angle: f32 },
+}
+
+/// Light source with a color, which fades out towards its radius.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Light {
+    pos: Vector2<f32>,
+    radius: f32,
+    color: Color,
+    intensity: f32,
+    falloff: f32,
+    shape: LightShape,
+    shadows: bool,
+}
+
+impl Light {
+    /// Create a new point light at `pos` with `radius` and `color`, which casts shadows.
+    #[inline]
+    pub fn new(pos: Vector2<f32>, radius: f32, color: Color) -> Self {
+        Self {
+            pos,
+            radius,
+            color,
+            intensity: 1.0,
+            falloff: 2.0,
+            shape: LightShape::Point,
+            shadows: true,
+        }
+    }
+
+    /// Create a new cone light at `pos`, pointing to `direction` with a spread of `angle`, both in radians.
+    #[inline]
+    pub fn cone(pos: Vector2<f32>, radius: f32, color: Color, direction: f32, angle: f32) -> Self {
+        Self::new(pos, radius, color).with_shape(LightShape::Cone { direction, angle })
+    }
+
+    /// Set the shape of the lit area. Default is [LightShape::Point].
+    #[inline]
+    pub fn with_shape(mut self, shape: LightShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Multiply the light color with `intensity`. Values above `1.0` can brighten the canvas.
+    #[inline]
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Set the exponent of the fade towards the radius. `1.0` fades linearly, default is `2.0`.
+    #[inline]
+    pub fn with_falloff(mut self, falloff: f32) -> Self {
+        self.falloff = falloff;
+        self
+    }
+
+    /// Set whether the light is blocked by occluders. Default is `true`.
+    #[inline]
+    pub fn with_shadows(mut self, shadows: bool) -> Self {
+        self.shadows = shadows;
+        self
+    }
+
+    /// Move the light to `pos`.
+    #[inline]
+    pub fn set_pos(&mut self, pos: Vector2<f32>) { self.pos = pos; }
+
+    /// Point a cone light to `direction` in radians. Does nothing for point lights.
+    #[inline]
+    pub fn set_direction(&mut self, direction: f32) {
+        if let LightShape::Cone { angle, .. } = self.shape {
+            self.shape = LightShape::Cone { direction, angle };
+        }
+    }
+
+    /// Get the position of the light.
+    #[inline]
+    pub fn pos(&self) -> Vector2<f32> { self.pos }
+
+    /// Amount of light reaching the offset `dx` and `dy` from the light, ignoring occluders.
+    #[inline]
+    fn attenuation(&self, dx: f32, dy: f32, cos_half: f32, direction: (f32, f32)) -> f32 {
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance >= self.radius {
+            return 0.0;
+        }
+
+        if let LightShape::Cone { .. } = self.shape {
+            if distance > 0.0 && (dx * direction.0 + dy * direction.1) / distance < cos_half {
+                return 0.0;
+            }
+        }
+
+        (1.0 - distance / self.radius).powf(self.falloff) * self.intensity
+    }
+}
+
+/// Closed polygon, which blocks light and casts hard shadows.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Occluder {
+    points: Vec<Vector2<f32>>,
+}
+
+impl Occluder {
+    /// Create a new occluder from the corners of a polygon. The last point connects to the first one.
+    #[inline]
+    pub fn polygon(points: Vec<Vector2<f32>>) -> Self { Self { points } }
+
+    /// Create a new rectangular occluder at `pos` with `width` and `height`.
+    #[inline]
+    pub fn rect(pos: Vector2<f32>, width: f32, height: f32) -> Self {
+        let (x, y) = (pos.x, pos.y);
+        Self::polygon(vec![
+            Vector2::from([x, y]),
+            Vector2::from([x + width, y]),
+            Vector2::from([x + width, y + height]),
+            Vector2::from([x, y + height]),
+        ])
+    }
+
+    /// Returns the corners of the polygon.
+    #[inline]
+    pub fn points(&self) -> &[Vector2<f32>] { &self.points }
+
+    /// Returns all edges of the polygon as start and end points.
+    fn edges(&self) -> impl Iterator<Item = (Vector2<f32>, Vector2<f32>)> + '_ {
+        let next = self.points.iter().cycle().skip(1);
+        self.points.iter().zip(next).map(|(a, b)| (*a, *b))
+    }
+}
+
+/// Lighting pass, which multiplies a light map over everything drawn before it.
+///
+/// The light map starts with the ambient color and accumulates the color of every [Light].
+/// Lights, which cast shadows, are blocked by [Occluder]s, which also shade their own pixels.
+/// Draw it after the scene, e.g. with [Canvas::drawable].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Lighting {
+    ambient: Color,
+    lights: Vec<Light>,
+    occluders: Vec<Occluder>,
+}
+
+impl Lighting {
+    /// Create a new lighting pass, where unlit areas get multiplied with `ambient`.
+    #[inline]
+    pub fn new(ambient: Color) -> Self {
+        Self {
+            ambient,
+            lights: Vec::new(),
+            occluders: Vec::new(),
+        }
+    }
+
+    /// Add a light.
+    #[inline]
+    pub fn with_light(mut self, light: Light) -> Self {
+        self.lights.push(light);
+        self
+    }
+
+    /// Add an occluder.
+    #[inline]
+    pub fn with_occluder(mut self, occluder: Occluder) -> Self {
+        self.occluders.push(occluder);
+        self
+    }
+
+    /// Add occluders for the walls of a tilemap.\
+    /// `solid` contains one entry per tile, row by row with `columns` tiles per row,
+    /// where `true` marks a wall of `tile_width` x `tile_height` pixels.
+    /// Neighboring walls in a row are merged into one occluder.
+    #[inline]
+    pub fn with_tiles(
+        mut self,
+        solid: &[bool],
+        columns: usize,
+        tile_width: f32,
+        tile_height: f32,
+    ) -> Self {
+        self.add_tiles(solid, columns, tile_width, tile_height);
+        self
+    }
+
+    /// Add a light and return its index.
+    #[inline]
+    pub fn add_light(&mut self, light: Light) -> usize {
+        self.lights.push(light);
+        self.lights.len() - 1
+    }
+
+    /// Add an occluder.
+    #[inline]
+    pub fn add_occluder(&mut self, occluder: Occluder) { self.occluders.push(occluder); }
+
+    /// Add occluders for the walls of a tilemap. See [Lighting::with_tiles].
+    pub fn add_tiles(&mut self, solid: &[bool], columns: usize, tile_width: f32, tile_height: f32) {
+        if columns == 0 {
+            return;
+        }
+
+        for (row, tiles) in solid.chunks(columns).enumerate() {
+            let mut column = 0;
+            while column < tiles.len() {
+                if !tiles[column] {
+                    column += 1;
+                    continue;
+                }
+
+                let start = column;
+                while column < tiles.len() && tiles[column] {
+                    column += 1;
+                }
+
+                self.occluders.push(Occluder::rect(
+                    Vector2::from([start as f32 * tile_width, row as f32 * tile_height]),
+                    (column - start) as f32 * tile_width,
+                    tile_height,
+                ));
+            }
+        }
+    }
+
+    /// Set the ambient color.
+    #[inline]
+    pub fn set_ambient(&mut self, ambient: Color) { self.ambient = ambient; }
+
+    /// Get the light at `index` as mutable.
+    #[inline]
+    pub fn light_mut(&mut self, index: usize) -> Option<&mut Light> { self.lights.get_mut(index) }
+
+    /// Returns all lights.
+    #[inline]
+    pub fn lights(&self) -> &[Light] { &self.lights }
+
+    /// Returns all occluders.
+    #[inline]
+    pub fn occluders(&self) -> &[Occluder] { &self.occluders }
+
+    /// Remove all lights.
+    #[inline]
+    pub fn clear_lights(&mut self) { self.lights.clear(); }
+
+    /// Remove all occluders.
+    #[inline]
+    pub fn clear_occluders(&mut self) { self.occluders.clear(); }
+
+    /// Collect the edges, which can block each light.
+    fn prepare(&self) -> Vec<PreparedLight> {
+        self.lights
+            .iter()
+            .filter(|light| light.radius > 0.0 && light.intensity > 0.0)
+            .map(|light| {
+                let (cos_half, direction) = match light.shape {
+                    LightShape::Point => (-1.0, (0.0, 0.0)),
+                    LightShape::Cone { direction, angle } => {
+                        ((angle / 2.0).cos(), (direction.cos(), direction.sin()))
+                    }
+                };
+
+                let (x, y, r) = (light.pos.x, light.pos.y, light.radius);
+                let edges = if light.shadows {
+                    self.occluders
+                        .iter()
+                        .flat_map(Occluder::edges)
+                        .filter(|(a, b)| {
+                            a.x.max(b.x) >= x - r
+                                && a.x.min(b.x) <= x + r
+                                && a.y.max(b.y) >= y - r
+                                && a.y.min(b.y) <= y + r
+                        })
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+
+                PreparedLight {
+                    light: *light,
+                    cos_half,
+                    direction,
+                    edges,
+                }
+            })
+            .collect()
+    }
+
+    /// Multiply the light map over the rows of `raster`.
+    fn rasterize(&self, raster: &mut Raster, lights: &[PreparedLight]) {
+        let width = raster.width();
+        let ambient = [
+            self.ambient.red() as f32 / 255.0,
+            self.ambient.green() as f32 / 255.0,
+            self.ambient.blue() as f32 / 255.0,
+        ];
+        let mut map = vec![ambient; width];
+
+        for y in raster.rows() {
+            map.fill(ambient);
+            let py = y as f32 + 0.5;
+
+            for prepared in lights {
+                let light = &prepared.light;
+                let dy = py - light.pos.y;
+                if dy.abs() >= light.radius {
+                    continue;
+                }
+
+                // only visit the pixels covered by the circle in this row
+                let half = (light.radius * light.radius - dy * dy).sqrt();
+                let start = (light.pos.x - half).floor().max(0.0) as usize;
+                let end = ((light.pos.x + half).ceil().max(0.0) as usize).min(width);
+
+                let color = [
+                    light.color.red() as f32 / 255.0,
+                    light.color.green() as f32 / 255.0,
+                    light.color.blue() as f32 / 255.0,
+                ];
+
+                for (x, value) in map.iter_mut().enumerate().take(end).skip(start) {
+                    let px = x as f32 + 0.5;
+                    let amount = light.attenuation(
+                        px - light.pos.x,
+                        py - light.pos.y,
+                        prepared.cos_half,
+                        prepared.direction,
+                    );
+
+                    if amount <= 0.0 || prepared.blocked(px, py) {
+                        continue;
+                    }
+
+                    value[0] += color[0] * amount;
+                    value[1] += color[1] * amount;
+                    value[2] += color[2] * amount;
+                }
+            }
+
+            raster.map_span(y, 0, width, |x, color| {
+                let [r, g, b] = map[x];
+                let scale = |channel: u8, light: f32| (channel as f32 * light).min(255.0) as u8;
+
+                Color::from_rgba(
+                    scale(color.red(), r),
+                    scale(color.green(), g),
+                    scale(color.blue(), b),
+                    color.alpha(),
+                )
+            });
+        }
+    }
+}
+
+/// A [Light] with its cone precomputed and the occluder edges in its reach.
+struct PreparedLight {
+    light: Light,
+    cos_half: f32,
+    direction: (f32, f32),
+    edges: Vec<(Vector2<f32>, Vector2<f32>)>,
+}
+
+impl PreparedLight {
+    /// Returns `true` if an edge crosses the ray from the light to `(x, y)`.
+    #[inline]
+    fn blocked(&self, x: f32, y: f32) -> bool {
+        let (ox, oy) = (self.light.pos.x, self.light.pos.y);
+        let (dx, dy) = (x - ox, y - oy);
+
+        self.edges.iter().any(|(a, b)| {
+            let (ex, ey) = (b.x - a.x, b.y - a.y);
+            let denominator = dx * ey - dy * ex;
+            if denominator == 0.0 {
+                return false;
+            }
+
+            let (ax, ay) = (a.x - ox, a.y - oy);
+            let t = (ax * ey - ay * ex) / denominator;
+            let u = (ax * dy - ay * dx) / denominator;
+            t > 0.0 && t < 1.0 && (0.0..=1.0).contains(&u)
+        })
+    }
+}
+
+impl Drawable for Lighting {
+    /// Multiply the light map over `canvas`.
+    #[inline]
+    fn apply(&self, canvas: &mut Canvas) {
+        let lights = self.prepare();
+
+        #[cfg(feature = "parallel")]
+        canvas.par_raster(|raster| self.rasterize(raster, &lights));
+
+        #[cfg(not(feature = "parallel"))]
+        self.rasterize(&mut canvas.raster(), &lights);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::canvas::Canvas;
+    use crate::render::color::Color;
+    use crate::render::lighting::{Light, Lighting, Occluder};
+    use crate::simplified::vec2;
+
+    fn lit(lighting: &Lighting) -> Canvas {
+        let mut canvas = Canvas::new(20, 10);
+        canvas.clear(&Color::WHITE);
+        canvas.drawable(lighting);
+        canvas
+    }
+
+    #[test]
+    fn multiplies_ambient_and_lights() {
+        let lighting = Lighting::new(Color::from_rgb(51, 51, 51))
+            .with_light(Light::new(vec2(5.0, 5.0), 4.0, Color::RED).with_falloff(1.0));
+        let canvas = lit(&lighting);
+
+        let center = canvas.get_pixel(5, 5).unwrap();
+        assert!(center.red() > 200);
+        assert_eq!(center.green(), 51);
+        assert_eq!(canvas.get_pixel(15, 5), Some(Color::from_rgb(51, 51, 51)));
+    }
+
+    #[test]
+    fn occluders_cast_shadows() {
+        let light = Light::new(vec2(2.0, 5.0), 20.0, Color::WHITE);
+        let wall = Occluder::rect(vec2(8.0, 0.0), 2.0, 10.0);
+
+        let lighting = Lighting::new(Color::BLACK)
+            .with_light(light)
+            .with_occluder(wall.clone());
+        let canvas = lit(&lighting);
+        assert!(canvas.get_pixel(6, 5).unwrap().red() > 0);
+        assert_eq!(canvas.get_pixel(14, 5), Some(Color::BLACK));
+
+        let lighting = Lighting::new(Color::BLACK)
+            .with_light(light.with_shadows(false))
+            .with_occluder(wall);
+        assert!(lit(&lighting).get_pixel(14, 5).unwrap().red() > 0);
+    }
+
+    #[test]
+    fn cone_lights_only_their_direction() {
+        let lighting = Lighting::new(Color::BLACK).with_light(Light::cone(
+            vec2(10.0, 5.0),
+            8.0,
+            Color::WHITE,
+            0.0,
+            1.0,
+        ));
+        let canvas = lit(&lighting);
+
+        assert!(canvas.get_pixel(13, 5).unwrap().red() > 0);
+        assert_eq!(canvas.get_pixel(7, 5), Some(Color::BLACK));
+    }
+
+    #[test]
+    fn merges_tile_walls() {
+        #[rustfmt::skip]
+        let tiles = [
+            true, true, false, true,
+            false, false, false, true,
+        ];
+        let lighting = Lighting::new(Color::BLACK).with_tiles(&tiles, 4, 8.0, 8.0);
+
+        assert_eq!(lighting.occluders().len(), 3);
+        assert_eq!(lighting.occluders()[0].points()[1], vec2(16.0, 0.0));
+    }
+}
@@ -1,8 +1,38 @@
+/// Contains the [bitmap_font::BitmapFont] struct to draw text without the `text` feature.
+pub mod bitmap_font;
+
 /// Contains the [canvas::Canvas] struct.
 pub mod canvas;
 
 /// Contains the [color::Color] struct.
 pub mod color; // TODO: move to `qilin::types` ?
 
+/// Contains the [damage::DirtyRect] and [damage::DamageStats] structs for dirty tracking.
+pub mod damage;
+
+/// Contains the [fill::Fill] enum and gradients.
+pub mod fill;
+
+/// Contains the [indexed::IndexedImage] struct and palettes for palette swapping and cycling.
+pub mod indexed;
+
+/// Contains the [lighting::Lighting] pass with lights and shadow casting occluders.
+pub mod lighting;
+
+/// Contains the [nine_slice::NineSlice] drawable for scalable UI frames.
+pub mod nine_slice;
+
+/// Contains the [post::PostProcess] pipeline for screen effects.
+pub mod post;
+
+/// Contains the internal [raster::Raster] view, used to rasterize into bands of rows.
+pub(crate) mod raster;
+
 /// Contains the [sketch::Sketch] struct.
 pub mod sketch;
+
+/// Contains the [target::CanvasImage] view, to draw off-screen canvases like images.
+pub mod target;
+
+/// Contains the [viewport::LogicalResolution] and [viewport::Viewport] structs for scaling low-res canvases.
+pub mod viewport;
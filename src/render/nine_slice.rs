@@ -0,0 +1,299 @@
+use crate::render::canvas::Canvas;
+use crate::render::sketch::Drawable;
+use crate::types::Image;
+use mint::Vector2;
+use std::sync::Arc;
+
+/// Border sizes of a [NineSlice] source image in pixels.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Insets {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+}
+
+impl Insets {
+    /// Create insets with the same size on every side.
+    #[inline]
+    pub fn uniform(size: u32) -> Self {
+        Self {
+            left: size,
+            top: size,
+            right: size,
+            bottom: size,
+        }
+    }
+}
+
+/// How the edges or the center of a [NineSlice] fill their destination.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum SliceMode {
+    /// Scale the source region to the destination size.
+    #[default]
+    Stretch,
+    /// Repeat the source region without scaling it.
+    Tile,
+}
+
+/// Image split into nine regions by [Insets], which can be drawn at any size without distorting its borders.
+///
+/// Corners are drawn unscaled, edges are stretched or tiled along one axis and the center
+/// along both axes. If the destination is smaller than the borders, the borders get cut proportionally.
+/// Useful for dialog boxes, buttons and other UI frames.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NineSlice {
+    data: Arc<Image>,
+    width: u32,
+    height: u32,
+    insets: Insets,
+    pos: Vector2<u32>,
+    dest_width: u32,
+    dest_height: u32,
+    edges: SliceMode,
+    center: SliceMode,
+}
+
+impl NineSlice {
+    /// Create a new nine-slice from image `data` with `width` and `height` and border `insets`.\
+    /// It gets drawn at `(0, 0)` with the size of the source, until [NineSlice::with_rect] is used.
+    /// Returns [None] if the data doesn't match the size or the insets don't fit into the image.
+    #[inline]
+    pub fn new(
+        data: impl Into<Arc<Image>>,
+        width: u32,
+        height: u32,
+        insets: Insets,
+    ) -> Option<Self> {
+        let data = data.into();
+        let horizontal = insets.left.checked_add(insets.right)?;
+        let vertical = insets.top.checked_add(insets.bottom)?;
+        if data.len() != width as usize * height as usize || horizontal > width || vertical > height
+        {
+            return None;
+        }
+
+        Some(Self {
+            data,
+            width,
+            height,
+            insets,
+            pos: Vector2::from([0, 0]),
+            dest_width: width,
+            dest_height: height,
+            edges: SliceMode::Stretch,
+            center: SliceMode::Stretch,
+        })
+    }
+
+    /// Draw at `pos` with the destination size `width` and `height`.
+    #[inline]
+    pub fn with_rect(mut self, pos: Vector2<u32>, width: u32, height: u32) -> Self {
+        self.set_rect(pos, width, height);
+        self
+    }
+
+    /// Set how the edges fill their destination. Default is [SliceMode::Stretch].
+    #[inline]
+    pub fn with_edges(mut self, mode: SliceMode) -> Self {
+        self.edges = mode;
+        self
+    }
+
+    /// Set how the center fills its destination. Default is [SliceMode::Stretch].
+    #[inline]
+    pub fn with_center(mut self, mode: SliceMode) -> Self {
+        self.center = mode;
+        self
+    }
+
+    /// Set the position and destination size.
+    #[inline]
+    pub fn set_rect(&mut self, pos: Vector2<u32>, width: u32, height: u32) {
+        self.pos = pos;
+        self.dest_width = width;
+        self.dest_height = height;
+    }
+
+    /// Get the border insets of the source image.
+    #[inline]
+    pub fn insets(&self) -> Insets { self.insets }
+}
+
+/// Source coordinates along one axis for both slice modes, and whether a pixel is inside of the center.
+struct Axis {
+    edge: Vec<usize>,
+    center: Vec<usize>,
+    inside: Vec<bool>,
+}
+
+impl Axis {
+    /// Map `dest` destination pixels to a source axis of `size` with borders `start` and `end`.
+    fn new(
+        size: u32,
+        start: u32,
+        end: u32,
+        dest: u32,
+        edges: SliceMode,
+        center: SliceMode,
+    ) -> Self {
+        let (size, dest) = (size as usize, dest as usize);
+        let (src_start, src_end) = (start as usize, end as usize);
+        let (mut start, mut end) = (src_start, src_end);
+
+        // cut the borders proportionally, if they don't fit
+        if start + end > dest {
+            start = dest * start / (start + end);
+            end = dest - start;
+        }
+
+        let middle = size - src_start - src_end;
+        let dest_middle = dest - start - end;
+
+        let map = |d: usize, mode: SliceMode| {
+            if d < start {
+                d
+            } else if d >= dest - end {
+                size - (dest - d)
+            } else if middle == 0 {
+                src_start.min(size - 1)
+            } else {
+                let offset = d - start;
+                src_start
+                    + match mode {
+                        SliceMode::Stretch => offset * middle / dest_middle,
+                        SliceMode::Tile => offset % middle,
+                    }
+            }
+        };
+
+        Self {
+            edge: (0..dest).map(|d| map(d, edges)).collect(),
+            center: (0..dest).map(|d| map(d, center)).collect(),
+            inside: (0..dest).map(|d| d >= start && d < dest - end).collect(),
+        }
+    }
+}
+
+impl Drawable for NineSlice {
+    /// Blend the nine regions on top of `canvas`.
+    #[inline]
+    fn apply(&self, canvas: &mut Canvas) {
+        if self.width == 0 || self.height == 0 || self.dest_width == 0 || self.dest_height == 0 {
+            return;
+        }
+
+        let columns = Axis::new(
+            self.width,
+            self.insets.left,
+            self.insets.right,
+            self.dest_width,
+            self.edges,
+            self.center,
+        );
+        let rows = Axis::new(
+            self.height,
+            self.insets.top,
+            self.insets.bottom,
+            self.dest_height,
+            self.edges,
+            self.center,
+        );
+
+        let (start_x, start_y) = (self.pos.x as usize, self.pos.y as usize);
+        let width = self.width as usize;
+        let mut raster = canvas.raster();
+        let mut row = Vec::with_capacity(self.dest_width as usize);
+
+        for y in raster.clip_rows(start_y, start_y + self.dest_height as usize) {
+            let dy = y - start_y;
+
+            // the center of a row is stretched like the center, the rest like the left or right edge
+            let (src_y_edge, src_y_center) = (rows.edge[dy], rows.center[dy]);
+            let columns_inside = if rows.inside[dy] {
+                &columns.center
+            } else {
+                &columns.edge
+            };
+
+            row.clear();
+            row.extend((0..self.dest_width as usize).map(|dx| {
+                let sy = if columns.inside[dx] {
+                    src_y_center
+                } else {
+                    src_y_edge
+                };
+                self.data[sy * width + columns_inside[dx]]
+            }));
+
+            raster.blend_span(y, start_x, &row);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::canvas::Canvas;
+    use crate::render::color::Color;
+    use crate::render::nine_slice::{Insets, NineSlice, SliceMode};
+    use crate::simplified::vec2;
+
+    /// 3x3 source with a different color per region.
+    fn source() -> Vec<Color> { (0..9).map(|i| Color::from_rgb(i * 20, 0, 0)).collect() }
+
+    fn pixels(canvas: &Canvas) -> Vec<u8> {
+        canvas
+            .get_buffer()
+            .as_slice()
+            .iter()
+            .map(|px| Color(*px).red() / 20)
+            .collect()
+    }
+
+    #[test]
+    fn keeps_corners_and_stretches_edges() {
+        let slice = NineSlice::new(source(), 3, 3, Insets::uniform(1))
+            .unwrap()
+            .with_rect(vec2(0, 0), 5, 4);
+        let mut canvas = Canvas::new(5, 4);
+        canvas.drawable(&slice);
+
+        #[rustfmt::skip]
+        assert_eq!(pixels(&canvas), vec![
+            0, 1, 1, 1, 2,
+            3, 4, 4, 4, 5,
+            3, 4, 4, 4, 5,
+            6, 7, 7, 7, 8,
+        ]);
+    }
+
+    #[test]
+    fn tiles_center() {
+        let source: Vec<Color> = (0..16).map(|i| Color::from_rgb(i * 10, 0, 0)).collect();
+        let slice = NineSlice::new(source, 4, 4, Insets::uniform(1))
+            .unwrap()
+            .with_center(SliceMode::Tile)
+            .with_rect(vec2(0, 0), 7, 3);
+        let mut canvas = Canvas::new(7, 3);
+        canvas.drawable(&slice);
+
+        let row: Vec<u8> = canvas.get_buffer().as_slice()[7..14]
+            .iter()
+            .map(|px| Color(*px).red() / 10)
+            .collect();
+        assert_eq!(row, vec![4, 5, 6, 5, 6, 5, 7]);
+    }
+
+    #[test]
+    fn rejects_invalid_insets() {
+        assert!(NineSlice::new(source(), 3, 3, Insets::uniform(2)).is_none());
+        assert!(NineSlice::new(source(), 4, 3, Insets::uniform(1)).is_none());
+
+        let overflowing = Insets {
+            left: u32::MAX,
+            right: 2,
+            ..Insets::default()
+        };
+        assert!(NineSlice::new(source(), 3, 3, overflowing).is_none());
+    }
+}
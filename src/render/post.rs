@@ -0,0 +1,457 @@
+use crate::math::buffer::Buffer;
+use crate::render::canvas::Canvas;
+use crate::render::color::Color;
+use crate::types::Image;
+
+/// 4x4 Bayer matrix for ordered dithering, with values from `0` to `15`.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// A chain of screen effects, applied to the canvas before it gets presented.
+///
+/// Use [crate::types::GameConfig::post_process] to set the initial chain and
+/// [crate::game::context::GameContext::post_process] to change it at runtime.
+#[derive(Clone, Debug, Default)]
+pub struct PostProcess {
+    passes: Vec<Pass>,
+}
+
+/// An [Effect] in a [PostProcess] chain, which can be toggled on and off.
+#[derive(Clone, Debug)]
+pub struct Pass {
+    pub effect: Effect,
+    pub enabled: bool,
+}
+
+impl PostProcess {
+    /// Create a new empty chain.
+    #[inline]
+    pub fn new() -> Self { Self { passes: Vec::new() } }
+
+    /// Append an enabled `effect` to the chain.
+    #[inline]
+    pub fn with(mut self, effect: Effect) -> Self {
+        self.push(effect);
+        self
+    }
+
+    /// Append an enabled `effect` to the chain and return its index.
+    #[inline]
+    pub fn push(&mut self, effect: Effect) -> usize {
+        self.passes.push(Pass {
+            effect,
+            enabled: true,
+        });
+        self.passes.len() - 1
+    }
+
+    /// Remove the pass at `index`.\
+    /// Returns [None] if the pass does not exist.
+    #[inline]
+    pub fn remove(&mut self, index: usize) -> Option<Effect> {
+        (index < self.passes.len()).then(|| self.passes.remove(index).effect)
+    }
+
+    /// Get the pass at `index`.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&Pass> { self.passes.get(index) }
+
+    /// Get the pass at `index` as mutable, e.g. to change the parameters of its effect.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Pass> { self.passes.get_mut(index) }
+
+    /// Enable or disable the pass at `index`.\
+    /// Returns [None] if the pass does not exist.
+    #[inline]
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) -> Option<()> {
+        self.passes.get_mut(index)?.enabled = enabled;
+        Some(())
+    }
+
+    /// Toggle the pass at `index` and return whether it is enabled now.\
+    /// Returns [None] if the pass does not exist.
+    #[inline]
+    pub fn toggle(&mut self, index: usize) -> Option<bool> {
+        let pass = self.passes.get_mut(index)?;
+        pass.enabled = !pass.enabled;
+        Some(pass.enabled)
+    }
+
+    /// Returns all passes of the chain.
+    #[inline]
+    pub fn passes(&self) -> &[Pass] { &self.passes }
+
+    /// Remove all passes.
+    #[inline]
+    pub fn clear(&mut self) { self.passes.clear(); }
+
+    /// Returns `true` if at least one pass is enabled.
+    #[inline]
+    pub fn is_active(&self) -> bool { self.passes.iter().any(|pass| pass.enabled) }
+
+    /// Apply all enabled passes to `canvas` in order.
+    #[inline]
+    pub fn apply(&self, canvas: &mut Canvas) {
+        for pass in self.passes.iter().filter(|pass| pass.enabled) {
+            pass.effect.apply(canvas);
+        }
+    }
+}
+
+/// A screen effect, which modifies the whole canvas.
+#[derive(Clone, Debug)]
+pub enum Effect {
+    /// Darken every `spacing`-th row like a CRT monitor.\
+    /// `intensity` ranges from `0.0` (no effect) to `1.0` (black rows).
+    Scanlines { spacing: usize, intensity: f32 },
+
+    /// Darken the edges of the screen.\
+    /// `radius` is the distance from the center (`0.0`) to the corners (`1.0`), where the darkening starts.
+    /// `strength` ranges from `0.0` (no effect) to `1.0` (black corners).
+    Vignette { radius: f32, strength: f32 },
+
+    /// Blur by averaging all pixels within `radius`.
+    BoxBlur { radius: usize },
+
+    /// Blur with a gaussian kernel with standard deviation `sigma`.
+    GaussianBlur { sigma: f32 },
+
+    /// Map every color through a [ColorLut].
+    ColorGrade(ColorLut),
+
+    /// Replace every color with the nearest color of `palette`.\
+    /// If `dither` is enabled, an ordered dither pattern is applied before quantizing.
+    Quantize { palette: Vec<Color>, dither: bool },
+
+    /// Reduce every channel to `levels` values using an ordered dither pattern.
+    Dither { levels: u8 },
+
+    /// Apply a custom function to the canvas.
+    Custom(fn(&mut Canvas)),
+}
+
+impl Effect {
+    /// Apply the effect to `canvas`.
+    #[inline]
+    pub fn apply(&self, canvas: &mut Canvas) {
+        let buffer = canvas.buffer_mut();
+
+        match self {
+            Effect::Scanlines { spacing, intensity } => {
+                scanlines(buffer, (*spacing).max(1), *intensity)
+            }
+            Effect::Vignette { radius, strength } => vignette(buffer, *radius, *strength),
+            Effect::BoxBlur { radius } => {
+                let kernel = vec![1.0; radius * 2 + 1];
+                convolve(buffer, &kernel);
+            }
+            Effect::GaussianBlur { sigma } => convolve(buffer, &gaussian_kernel(*sigma)),
+            Effect::ColorGrade(lut) => map_pixels(buffer, |_, _, color| lut.map(color)),
+            Effect::Quantize { palette, dither } => {
+                if palette.is_empty() {
+                    return;
+                }
+
+                map_pixels(buffer, |x, y, color| {
+                    let color = if *dither {
+                        // spread the threshold over the average distance between palette colors
+                        let spread = 255.0 / palette.len() as f32;
+                        offset(color, bayer(x, y) * spread)
+                    } else {
+                        color
+                    };
+
+                    nearest(palette, color).with_alpha(color.alpha())
+                });
+            }
+            Effect::Dither { levels } => {
+                let steps = (*levels).max(2) as f32 - 1.0;
+                let quantize = |c: u8, threshold: f32| {
+                    let value = c as f32 / 255.0 * steps + threshold;
+                    (value.floor().clamp(0.0, steps) / steps * 255.0).round() as u8
+                };
+
+                map_pixels(buffer, |x, y, color| {
+                    let threshold = bayer(x, y) + 0.5;
+                    let (r, g, b, a) = color.to_rgba();
+                    Color::from_rgba(
+                        quantize(r, threshold),
+                        quantize(g, threshold),
+                        quantize(b, threshold),
+                        a,
+                    )
+                });
+            }
+            Effect::Custom(f) => f(canvas),
+        }
+    }
+}
+
+/// A 3D color lookup table for color grading.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorLut {
+    size: usize,
+    data: Vec<Color>,
+}
+
+impl ColorLut {
+    /// Create a lookup table with `size` entries per channel, which doesn't change any colors.\
+    /// Colors are mapped exactly if `size - 1` divides `255`, e.g. for `16` or `18` entries.
+    #[inline]
+    pub fn identity(size: usize) -> Self { Self::from_fn(size, |color| color) }
+
+    /// Create a lookup table with `size` entries per channel by mapping every entry with `f`.
+    #[inline]
+    pub fn from_fn(size: usize, f: impl Fn(Color) -> Color) -> Self {
+        let size = size.max(2);
+        let step = |i: usize| (i * 255 / (size - 1)) as u8;
+
+        let mut data = Vec::with_capacity(size * size * size);
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    data.push(f(Color::from_rgb(step(r), step(g), step(b))));
+                }
+            }
+        }
+
+        Self { size, data }
+    }
+
+    /// Create a lookup table from an image strip with `size * size` width and `size` height.\
+    /// The strip contains `size` squares next to each other, one per blue value,
+    /// where red increases to the right and green increases downwards.
+    /// Returns [None] if the image has the wrong size.
+    #[inline]
+    pub fn from_image(size: usize, image: &Image) -> Option<Self> {
+        if size < 2 || image.len() != size * size * size {
+            return None;
+        }
+
+        let width = size * size;
+        let mut data = Vec::with_capacity(image.len());
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    data.push(image[g * width + b * size + r]);
+                }
+            }
+        }
+
+        Some(Self { size, data })
+    }
+
+    /// Returns the number of entries per channel.
+    #[inline]
+    pub fn size(&self) -> usize { self.size }
+
+    /// Map `color` through the lookup table with trilinear interpolation, keeping alpha.
+    #[inline]
+    pub fn map(&self, color: Color) -> Color {
+        let scale = (self.size - 1) as f32 / 255.0;
+        let (r, g, b) = color.to_rgb();
+        let (r, g, b) = (r as f32 * scale, g as f32 * scale, b as f32 * scale);
+
+        let (r0, g0, b0) = (r as usize, g as usize, b as usize);
+        let (r1, g1, b1) = (
+            (r0 + 1).min(self.size - 1),
+            (g0 + 1).min(self.size - 1),
+            (b0 + 1).min(self.size - 1),
+        );
+        let (tr, tg, tb) = (r.fract(), g.fract(), b.fract());
+
+        let at = |r: usize, g: usize, b: usize| self.data[(b * self.size + g) * self.size + r];
+        let plane = |b: usize| {
+            let low = at(r0, g0, b).lerp(at(r1, g0, b), tr);
+            let high = at(r0, g1, b).lerp(at(r1, g1, b), tr);
+            low.lerp(high, tg)
+        };
+
+        plane(b0).lerp(plane(b1), tb).with_alpha(color.alpha())
+    }
+}
+
+/// Replace every pixel of `buffer` with the result of `f(x, y, color)`.
+#[inline]
+fn map_pixels(buffer: &mut Buffer, f: impl Fn(usize, usize, Color) -> Color) {
+    let width = buffer.width;
+    if width == 0 {
+        return;
+    }
+
+    for (y, row) in buffer.buffer.chunks_exact_mut(width).enumerate() {
+        for (x, px) in row.iter_mut().enumerate() {
+            *px = f(x, y, Color(*px)).0;
+        }
+    }
+}
+
+/// Darken every `spacing`-th row.
+#[inline]
+fn scanlines(buffer: &mut Buffer, spacing: usize, intensity: f32) {
+    let factor = 1.0 - intensity.clamp(0.0, 1.0);
+    map_pixels(buffer, |_, y, color| {
+        if y % spacing == 0 {
+            color * factor
+        } else {
+            color
+        }
+    });
+}
+
+/// Darken the edges of the buffer.
+#[inline]
+fn vignette(buffer: &mut Buffer, radius: f32, strength: f32) {
+    let (cx, cy) = (buffer.width as f32 / 2.0, buffer.height as f32 / 2.0);
+    let max_distance = (cx * cx + cy * cy).sqrt().max(f32::EPSILON);
+    let radius = radius.clamp(0.0, 1.0);
+    let strength = strength.clamp(0.0, 1.0);
+
+    map_pixels(buffer, |x, y, color| {
+        let dx = x as f32 + 0.5 - cx;
+        let dy = y as f32 + 0.5 - cy;
+        let distance = (dx * dx + dy * dy).sqrt() / max_distance;
+
+        if distance <= radius {
+            return color;
+        }
+
+        // smoothstep from the radius to the corners
+        let t = ((distance - radius) / (1.0 - radius).max(f32::EPSILON)).clamp(0.0, 1.0);
+        color * (1.0 - strength * t * t * (3.0 - 2.0 * t))
+    });
+}
+
+/// Build a normalized gaussian kernel with standard deviation `sigma`.
+#[inline]
+fn gaussian_kernel(sigma: f32) -> Vec<f32> {
+    let sigma = sigma.max(0.01);
+    let radius = (sigma * 3.0).ceil() as isize;
+
+    (-radius..=radius)
+        .map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect()
+}
+
+/// Blur `buffer` horizontally and vertically with a symmetric `kernel`. Edges get clamped.
+#[inline]
+fn convolve(buffer: &mut Buffer, kernel: &[f32]) {
+    let (width, height) = (buffer.width, buffer.height);
+    if width == 0 || height == 0 || kernel.len() <= 1 {
+        return;
+    }
+
+    let radius = (kernel.len() / 2) as isize;
+    let total: f32 = kernel.iter().sum();
+    let mut scratch = vec![0; buffer.buffer.len()];
+
+    let blur = |source: &[u32], index: &dyn Fn(isize) -> usize| {
+        let mut sum = [0.0f32; 4];
+        for (k, weight) in kernel.iter().enumerate() {
+            let px = source[index(k as isize - radius)];
+            for (channel, value) in sum.iter_mut().enumerate() {
+                *value += ((px >> (channel * 8)) & 0xFF) as f32 * weight;
+            }
+        }
+
+        sum.iter().enumerate().fold(0, |out, (channel, value)| {
+            out | (((value / total).round() as u32).min(255) << (channel * 8))
+        })
+    };
+
+    // horizontal pass
+    for y in 0..height {
+        let row = y * width;
+        for x in 0..width {
+            scratch[row + x] = blur(&buffer.buffer, &|offset| {
+                row + (x as isize + offset).clamp(0, width as isize - 1) as usize
+            });
+        }
+    }
+
+    // vertical pass
+    for y in 0..height {
+        for x in 0..width {
+            buffer.buffer[y * width + x] = blur(&scratch, &|offset| {
+                (y as isize + offset).clamp(0, height as isize - 1) as usize * width + x
+            });
+        }
+    }
+}
+
+/// Get the dither threshold at `x` and `y` from `-0.5` to `0.5`.
+#[inline]
+fn bayer(x: usize, y: usize) -> f32 { (BAYER[y % 4][x % 4] as f32 + 0.5) / 16.0 - 0.5 }
+
+/// Add `amount` to every RGB channel of `color`.
+#[inline]
+fn offset(color: Color, amount: f32) -> Color {
+    let shift = |c: u8| (c as f32 + amount).round().clamp(0.0, 255.0) as u8;
+    let (r, g, b, a) = color.to_rgba();
+    Color::from_rgba(shift(r), shift(g), shift(b), a)
+}
+
+/// Find the color of `palette` closest to `color`.
+#[inline]
+fn nearest(palette: &[Color], color: Color) -> Color {
+    let (r, g, b) = color.to_rgb();
+    let distance = |other: &&Color| {
+        let (or, og, ob) = other.to_rgb();
+        let (dr, dg, db) = (
+            r as i32 - or as i32,
+            g as i32 - og as i32,
+            b as i32 - ob as i32,
+        );
+        dr * dr + dg * dg + db * db
+    };
+
+    *palette.iter().min_by_key(distance).unwrap_or(&color)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::canvas::Canvas;
+    use crate::render::color::Color;
+    use crate::render::post::{ColorLut, Effect, PostProcess};
+
+    #[test]
+    fn identity_lut_keeps_colors() {
+        let lut = ColorLut::identity(16);
+        for color in [Color::RED, Color::from_rgba(12, 200, 99, 40), Color::WHITE] {
+            assert_eq!(lut.map(color), color);
+        }
+    }
+
+    #[test]
+    fn blur_keeps_uniform_canvas() {
+        let mut canvas = Canvas::new(16, 8);
+        canvas.clear(&Color::from_rgb(10, 120, 240));
+
+        Effect::GaussianBlur { sigma: 1.5 }.apply(&mut canvas);
+        Effect::BoxBlur { radius: 2 }.apply(&mut canvas);
+        assert!(canvas
+            .get_buffer()
+            .as_slice()
+            .iter()
+            .all(|px| *px == Color::from_rgb(10, 120, 240).0));
+    }
+
+    #[test]
+    fn disabled_passes_are_skipped() {
+        let mut post = PostProcess::new().with(Effect::Quantize {
+            palette: vec![Color::BLACK],
+            dither: false,
+        });
+        assert_eq!(post.toggle(0), Some(false));
+        assert!(!post.is_active());
+        assert_eq!(post.toggle(1), None);
+
+        let mut canvas = Canvas::new(4, 4);
+        canvas.clear(&Color::WHITE);
+        post.apply(&mut canvas);
+        assert_eq!(canvas.get_pixel(1, 1), Some(Color::WHITE));
+
+        post.set_enabled(0, true);
+        post.apply(&mut canvas);
+        assert_eq!(canvas.get_pixel(1, 1), Some(Color::BLACK));
+    }
+}
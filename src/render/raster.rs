@@ -0,0 +1,216 @@
+use crate::render::color::Color;
+use crate::render::damage::RowDamage;
+use crate::render::fill::Fill;
+use std::ops::Range;
+
+/// Mutable view of a band of rows of a framebuffer.
+///
+/// Coordinates are always in canvas space, so operations can rasterize the same way into the
+/// whole canvas or into a single band. Pixels outside of the band are ignored.\
+/// If the view has damage rows, every write gets tracked for dirty-rectangle tracking.
+pub(crate) struct Raster<'a> {
+    pixels: &'a mut [u32],
+    width: usize,
+    rows: Range<usize>,
+    damage: Option<&'a mut [RowDamage]>,
+}
+
+impl<'a> Raster<'a> {
+    /// Create a view of `pixels`, which contain the rows starting at `top` of a framebuffer with `width`.
+    #[inline]
+    pub(crate) fn new(pixels: &'a mut [u32], width: usize, top: usize) -> Self {
+        let height = pixels.len().checked_div(width).unwrap_or(0);
+
+        Self {
+            pixels,
+            width,
+            rows: top..top + height,
+            damage: None,
+        }
+    }
+
+    /// Track writes in `damage`, which contains one entry per row of this view.
+    #[inline]
+    pub(crate) fn with_damage(mut self, damage: Option<&'a mut [RowDamage]>) -> Self {
+        self.damage = damage;
+        self
+    }
+
+    /// Mark the columns `start` to `end` (exclusive) of row `y` as drawn.
+    #[inline]
+    fn mark(&mut self, y: usize, start: usize, end: usize) {
+        if let Some(damage) = &mut self.damage {
+            damage[y - self.rows.start].mark(start, end);
+        }
+    }
+
+    /// Width of the rows in pixels.
+    #[inline]
+    pub(crate) fn width(&self) -> usize { self.width }
+
+    /// Range of rows contained in this view.
+    #[inline]
+    pub(crate) fn rows(&self) -> Range<usize> { self.rows.clone() }
+
+    /// Clip the row range `start..end` to the rows of this view.
+    #[inline]
+    pub(crate) fn clip_rows(&self, start: usize, end: usize) -> Range<usize> {
+        start.max(self.rows.start)..end.min(self.rows.end)
+    }
+
+    /// Get the index of the pixel at `x` and `y` or [None] if it's outside of this view.
+    #[inline]
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.width && self.rows.contains(&y) {
+            Some((y - self.rows.start) * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Get the pixels of row `y` or [None] if it's outside of this view.
+    #[inline]
+    fn row_mut(&mut self, y: usize) -> Option<&mut [u32]> {
+        if self.rows.contains(&y) {
+            let start = (y - self.rows.start) * self.width;
+            Some(&mut self.pixels[start..start + self.width])
+        } else {
+            None
+        }
+    }
+
+    /// Set every pixel of this view to `color`.
+    #[cfg(feature = "parallel")]
+    #[inline]
+    pub(crate) fn fill(&mut self, color: &Color) {
+        self.pixels.fill(color.0);
+
+        let width = self.width;
+        if let Some(damage) = &mut self.damage {
+            damage.iter_mut().for_each(|row| row.mark(0, width));
+        }
+    }
+
+    /// Fill the rectangle at `x` and `y` with `width` and `height`, which may start left of
+    /// or above the canvas, with `fill`.
+    #[inline]
+    pub(crate) fn fill_rect(&mut self, x: i64, y: i64, width: i64, height: i64, fill: &Fill) {
+        let (end_x, end_y) = (x + width, y + height);
+        if end_x <= 0 || end_y <= 0 {
+            return;
+        }
+
+        for row in self.clip_rows(y.max(0) as usize, end_y as usize) {
+            self.fill_span(row, x.max(0) as usize, end_x as usize, fill);
+        }
+    }
+
+    /// Fill the circle with center `cx` and `cy` and `radius`, which may be partly outside of the canvas, with `fill`.
+    #[inline]
+    pub(crate) fn fill_circle(&mut self, cx: i64, cy: i64, radius: i64, fill: &Fill) {
+        let start_y = (cy - radius).max(self.rows.start as i64);
+        let end_y = (cy + radius).min(self.rows.end as i64 - 1);
+
+        for y in start_y..=end_y {
+            // half width of the circle in this row
+            let dy = y - cy;
+            let half = ((radius * radius - dy * dy) as f64).sqrt() as i64;
+
+            let from = (cx - half).max(0) as usize;
+            let to = (cx + half + 1).max(0) as usize;
+
+            self.fill_span(y as usize, from, to, fill);
+        }
+    }
+
+    /// Fill the pixels of row `y` from `start_x` to `end_x` (exclusive) with `fill`.\
+    /// Opaque solid colors are written as a whole slice, everything else gets blended per pixel.
+    #[inline]
+    pub(crate) fn fill_span(&mut self, y: usize, start_x: usize, end_x: usize, fill: &Fill) {
+        let end_x = end_x.min(self.width);
+        if start_x >= end_x {
+            return;
+        }
+
+        if !self.rows.contains(&y) {
+            return;
+        }
+        self.mark(y, start_x, end_x);
+
+        let row = match self.row_mut(y) {
+            Some(row) => &mut row[start_x..end_x],
+            None => return,
+        };
+
+        match fill {
+            Fill::Solid(color) if color.is_opaque() => row.fill(color.0),
+            Fill::Solid(color) => {
+                for px in row {
+                    *px = Color(*px).blend(*color).0;
+                }
+            }
+            _ => {
+                let py = y as f32 + 0.5;
+                for (x, px) in (start_x..end_x).zip(row) {
+                    *px = Color(*px).blend(fill.color_at(x as f32 + 0.5, py)).0;
+                }
+            }
+        }
+    }
+
+    /// Blend `colors` on top of the pixels of row `y`, starting at `start_x`.
+    #[inline]
+    pub(crate) fn blend_span(&mut self, y: usize, start_x: usize, colors: &[Color]) {
+        let width = self.width;
+        if start_x >= width || !self.rows.contains(&y) {
+            return;
+        }
+        self.mark(y, start_x, width.min(start_x + colors.len()));
+
+        let row = match self.row_mut(y) {
+            Some(row) => &mut row[start_x..],
+            None => return,
+        };
+
+        for (px, color) in row.iter_mut().zip(colors) {
+            *px = Color(*px).blend(*color).0;
+        }
+    }
+
+    /// Replace every pixel of row `y` from `start_x` to `end_x` with the result of `f(x, color)`.
+    #[inline]
+    pub(crate) fn map_span(
+        &mut self,
+        y: usize,
+        start_x: usize,
+        end_x: usize,
+        mut f: impl FnMut(usize, Color) -> Color,
+    ) {
+        let end_x = end_x.min(self.width);
+        if start_x >= end_x || !self.rows.contains(&y) {
+            return;
+        }
+        self.mark(y, start_x, end_x);
+
+        if let Some(row) = self.row_mut(y) {
+            for (x, px) in row[start_x..end_x].iter_mut().enumerate() {
+                *px = f(start_x + x, Color(*px)).0;
+            }
+        }
+    }
+
+    /// Blend `color` on top of the pixel at `x` and `y`.
+    #[inline]
+    pub(crate) fn blend_pixel(&mut self, x: usize, y: usize, color: &Color) {
+        if let Some(index) = self.index(x, y) {
+            self.mark(y, x, x + 1);
+
+            let px = &mut self.pixels[index];
+            *px = if color.is_opaque() {
+                color.0
+            } else {
+                Color(*px).blend(*color).0
+            };
+        }
+    }
+}
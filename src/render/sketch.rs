@@ -1,83 +1,161 @@
 use crate::render::canvas::Canvas;
 use crate::render::color::Color;
+use crate::render::fill::{shift, Fill};
+use crate::render::raster::Raster;
+use crate::render::target::blit;
 use crate::types::Image;
 
 use mint::Vector2;
+use std::sync::Arc;
+
+/// Minimum number of pixels of a rectangle or image to render it in parallel.
+#[cfg(feature = "parallel")]
+const PARALLEL_MIN_PIXELS: usize = 128 * 128;
+
+/// A list of drawing [Operation]s to apply to a [Canvas].
+///
+/// A sketch can be built once and drawn every frame. Use [Sketch::last_handle] to get a
+/// [OpHandle] of an operation and modify it later, instead of rebuilding the whole sketch.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Sketch {
+    pub(crate) operations: Vec<Operation>,
+    pub(crate) visible: Vec<bool>,
+}
 
-#[derive(Clone, Debug, Default, Eq, Ord, PartialOrd, PartialEq)]
-pub struct Sketch(pub(crate) Vec<Operation>);
+/// Handle to an [Operation] inside a [Sketch].\
+/// Handles stay valid until [Sketch::clear] is called.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct OpHandle(usize);
 
 impl Sketch {
     /// Create a new empty sketch
     #[inline]
-    pub fn new() -> Self { Self(Vec::new()) }
+    pub fn new() -> Self {
+        Self {
+            operations: Vec::new(),
+            visible: Vec::new(),
+        }
+    }
+
+    /// Create a new empty sketch with space for `capacity` operations.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            operations: Vec::with_capacity(capacity),
+            visible: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Add an [Operation] to the sketch.
+    #[inline]
+    pub fn push(&mut self, operation: Operation) -> &mut Sketch {
+        self.operations.push(operation);
+        self.visible.push(true);
+        self
+    }
 
     /// Draw a line from `from` to `to` with color `color`
     #[inline]
     pub fn line(&mut self, from: Vector2<u32>, to: Vector2<u32>, color: Color) -> &mut Sketch {
-        self.0.push(Operation::Line { from, to, color });
-        self
+        self.push(Operation::Line { from, to, color })
     }
 
-    /// Draw a circle at `pos` with radius `radius` and color `color`.
+    /// Draw a circle at `pos` with radius `radius` and fill `fill`.\
+    /// `fill` can be a [Color] or a gradient.
     #[inline]
-    pub fn circle(&mut self, pos: Vector2<u32>, radius: u32, color: Color) -> &mut Sketch {
-        self.0.push(Operation::Circle { pos, radius, color });
-        self
+    pub fn circle(&mut self, pos: Vector2<u32>, radius: u32, fill: impl Into<Fill>) -> &mut Sketch {
+        self.push(Operation::Circle {
+            pos,
+            radius,
+            fill: fill.into(),
+        })
     }
 
-    /// Draw a rectangle at `pos` with width `width` and height `height` and color `color`.
+    /// Draw a rectangle at `pos` with width `width` and height `height` and fill `fill`.\
+    /// `fill` can be a [Color] or a gradient.
     #[inline]
     pub fn rect(
         &mut self,
         pos: Vector2<u32>,
         width: u32,
         height: u32,
-        color: Color,
+        fill: impl Into<Fill>,
     ) -> &mut Sketch {
-        self.0.push(Operation::Rect {
+        self.push(Operation::Rect {
             pos,
             width,
             height,
-            color,
-        });
-        self
+            fill: fill.into(),
+        })
     }
 
-    /// Draw an image at `pos` with width `width` and height `height` and data `data`.
+    /// Draw an image at `pos` with width `width` and height `height` and data `data`.\
+    /// Pass an `Arc<Image>` to share the pixel data between sketches without copying it.\
+    /// Images with less `data` than `width * height` pixels are skipped.
     #[inline]
     pub fn image(
         &mut self,
         pos: Vector2<u32>,
         width: u32,
         height: u32,
-        data: Vec<Color>,
+        data: impl Into<Arc<Image>>,
     ) -> &mut Sketch {
-        self.0.push(Operation::Image {
+        self.push(Operation::Image {
             pos,
             width,
             height,
-            data,
-        });
-        self
+            data: data.into(),
+        })
+    }
+
+    /// Draw an off-screen canvas at `pos`, scaled to width `width` and height `height`.\
+    /// `opacity` multiplies the alpha of every pixel, where `255` keeps the source alpha.
+    /// The canvas is shared, use [Arc::make_mut] to draw to it again.
+    #[inline]
+    pub fn canvas(
+        &mut self,
+        pos: Vector2<u32>,
+        width: u32,
+        height: u32,
+        source: impl Into<Arc<Canvas>>,
+        opacity: u8,
+    ) -> &mut Sketch {
+        self.push(Operation::Canvas {
+            pos,
+            width,
+            height,
+            source: source.into(),
+            opacity,
+        })
     }
 
-    /// Draw an oval at `pos` with width `width` and height `height` and color `color`.
+    /// Draw an oval at `pos` with width `width` and height `height` and fill `fill`.\
+    /// `fill` can be a [Color] or a gradient.
     #[inline]
     pub fn oval(
         &mut self,
         pos: Vector2<u32>,
         width: u32,
         height: u32,
-        color: Color,
+        fill: impl Into<Fill>,
     ) -> &mut Sketch {
-        self.0.push(Operation::Oval {
+        self.push(Operation::Oval {
             pos,
             width,
             height,
-            color,
-        });
-        self
+            fill: fill.into(),
+        })
+    }
+
+    /// Draw a polygon through `points` with fill `fill`.\
+    /// The polygon gets closed automatically and is filled using the even-odd rule.\
+    /// `fill` can be a [Color] or a gradient.
+    #[inline]
+    pub fn polygon(&mut self, points: Vec<Vector2<u32>>, fill: impl Into<Fill>) -> &mut Sketch {
+        self.push(Operation::Polygon {
+            points,
+            fill: fill.into(),
+        })
     }
 
     /// Just returns [Sketch] as &mut.\
@@ -85,11 +163,112 @@ impl Sketch {
     #[inline]
     pub fn empty(&mut self) -> &mut Sketch { self }
 
-    pub fn get_operations(&self) -> &Vec<Operation> { &self.0 }
+    pub fn get_operations(&self) -> &Vec<Operation> { &self.operations }
+
+    /// Iterate over all visible operations.
+    #[inline]
+    pub fn visible_operations(&self) -> impl Iterator<Item = &Operation> {
+        self.operations
+            .iter()
+            .zip(&self.visible)
+            .filter_map(|(op, visible)| visible.then_some(op))
+    }
+
+    /// Returns the [OpHandle] of the last added operation or [None] if the sketch is empty.
+    #[inline]
+    pub fn last_handle(&self) -> Option<OpHandle> {
+        self.operations.len().checked_sub(1).map(OpHandle)
+    }
+
+    /// Get the operation of `handle`.
+    #[inline]
+    pub fn get(&self, handle: OpHandle) -> Option<&Operation> { self.operations.get(handle.0) }
+
+    /// Get the operation of `handle` as mutable.
+    #[inline]
+    pub fn get_mut(&mut self, handle: OpHandle) -> Option<&mut Operation> {
+        self.operations.get_mut(handle.0)
+    }
+
+    /// Move the operation of `handle` by `dx` and `dy`.\
+    /// Returns [None] if the handle is invalid.
+    #[inline]
+    pub fn translate(&mut self, handle: OpHandle, dx: i32, dy: i32) -> Option<()> {
+        self.get_mut(handle)?.translate(dx, dy);
+        Some(())
+    }
+
+    /// Change the fill of the operation of `handle`.\
+    /// Returns [None] if the handle is invalid or the operation can not be filled,
+    /// like an [Operation::Image] or an [Operation::Line] with a gradient.
+    #[inline]
+    pub fn set_fill(&mut self, handle: OpHandle, fill: impl Into<Fill>) -> Option<()> {
+        match (self.get_mut(handle)?, fill.into()) {
+            (Operation::Line { color, .. }, Fill::Solid(new)) => *color = new,
+            (Operation::Circle { fill, .. }, new)
+            | (Operation::Rect { fill, .. }, new)
+            | (Operation::Oval { fill, .. }, new)
+            | (Operation::Polygon { fill, .. }, new) => *fill = new,
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Set whether the operation of `handle` is drawn.\
+    /// Returns [None] if the handle is invalid.
+    #[inline]
+    pub fn set_visible(&mut self, handle: OpHandle, visible: bool) -> Option<()> {
+        *self.visible.get_mut(handle.0)? = visible;
+        Some(())
+    }
+
+    /// Returns whether the operation of `handle` is drawn or [None] if the handle is invalid.
+    #[inline]
+    pub fn is_visible(&self, handle: OpHandle) -> Option<bool> {
+        self.visible.get(handle.0).copied()
+    }
+
+    /// Returns the number of operations.
+    #[inline]
+    pub fn len(&self) -> usize { self.operations.len() }
+
+    /// Returns `true` if the sketch contains no operations.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.operations.is_empty() }
+
+    /// Remove all operations, but keep the allocated memory to reuse it.\
+    /// Invalidates all [OpHandle]s.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.operations.clear();
+        self.visible.clear();
+    }
+}
+
+impl Drawable for Sketch {
+    /// Apply all visible operations to a [Canvas].\
+    /// With the `parallel` feature, the canvas gets split into bands of rows, which are rendered in parallel.
+    #[inline]
+    fn apply(&self, canvas: &mut Canvas) {
+        #[cfg(feature = "parallel")]
+        canvas.par_raster(|raster| {
+            for op in self.visible_operations() {
+                op.rasterize(raster);
+            }
+        });
+
+        #[cfg(not(feature = "parallel"))]
+        {
+            let mut raster = canvas.raster();
+            for op in self.visible_operations() {
+                op.rasterize(&mut raster);
+            }
+        }
+    }
 }
 
 /// A drawing operation to apply to a [Canvas] using a [Sketch].
-#[derive(Clone, Debug, Eq, Ord, PartialOrd, PartialEq)]
+#[derive(Clone, Debug)]
 pub enum Operation {
     Line {
         from: Vector2<u32>,
@@ -100,29 +279,174 @@ pub enum Operation {
     Circle {
         pos: Vector2<u32>,
         radius: u32,
-        color: Color,
+        fill: Fill,
     },
 
     Rect {
         pos: Vector2<u32>,
         width: u32,
         height: u32,
-        color: Color,
+        fill: Fill,
     },
 
     Image {
         pos: Vector2<u32>,
         width: u32,
         height: u32,
-        data: Image,
+        data: Arc<Image>,
     },
 
     Oval {
         pos: Vector2<u32>,
         width: u32,
         height: u32,
-        color: Color,
+        fill: Fill,
+    },
+
+    Polygon {
+        points: Vec<Vector2<u32>>,
+        fill: Fill,
     },
+
+    Canvas {
+        pos: Vector2<u32>,
+        width: u32,
+        height: u32,
+        source: Arc<Canvas>,
+        opacity: u8,
+    },
+}
+
+impl PartialEq for Operation {
+    /// Operations are equal, if they are the same kind with equal fields.\
+    /// Canvases are compared by identity instead of by their pixels.
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Operation::Line { from, to, color },
+                Operation::Line {
+                    from: other_from,
+                    to: other_to,
+                    color: other_color,
+                },
+            ) => from == other_from && to == other_to && color == other_color,
+            (
+                Operation::Circle { pos, radius, fill },
+                Operation::Circle {
+                    pos: other_pos,
+                    radius: other_radius,
+                    fill: other_fill,
+                },
+            ) => pos == other_pos && radius == other_radius && fill == other_fill,
+            (
+                Operation::Rect {
+                    pos,
+                    width,
+                    height,
+                    fill,
+                },
+                Operation::Rect {
+                    pos: other_pos,
+                    width: other_width,
+                    height: other_height,
+                    fill: other_fill,
+                },
+            )
+            | (
+                Operation::Oval {
+                    pos,
+                    width,
+                    height,
+                    fill,
+                },
+                Operation::Oval {
+                    pos: other_pos,
+                    width: other_width,
+                    height: other_height,
+                    fill: other_fill,
+                },
+            ) => {
+                pos == other_pos
+                    && width == other_width
+                    && height == other_height
+                    && fill == other_fill
+            }
+            (
+                Operation::Image {
+                    pos,
+                    width,
+                    height,
+                    data,
+                },
+                Operation::Image {
+                    pos: other_pos,
+                    width: other_width,
+                    height: other_height,
+                    data: other_data,
+                },
+            ) => {
+                pos == other_pos
+                    && width == other_width
+                    && height == other_height
+                    && data == other_data
+            }
+            (
+                Operation::Polygon { points, fill },
+                Operation::Polygon {
+                    points: other_points,
+                    fill: other_fill,
+                },
+            ) => points == other_points && fill == other_fill,
+            (
+                Operation::Canvas {
+                    pos,
+                    width,
+                    height,
+                    source,
+                    opacity,
+                },
+                Operation::Canvas {
+                    pos: other_pos,
+                    width: other_width,
+                    height: other_height,
+                    source: other_source,
+                    opacity: other_opacity,
+                },
+            ) => {
+                pos == other_pos
+                    && width == other_width
+                    && height == other_height
+                    && Arc::ptr_eq(source, other_source)
+                    && opacity == other_opacity
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Operation {
+    /// Move the operation by `dx` and `dy`, including its gradient.
+    #[inline]
+    pub fn translate(&mut self, dx: i32, dy: i32) {
+        match self {
+            Operation::Line { from, to, .. } => {
+                shift(from, dx, dy);
+                shift(to, dx, dy);
+            }
+            Operation::Image { pos, .. } | Operation::Canvas { pos, .. } => shift(pos, dx, dy),
+            Operation::Circle { pos, fill, .. }
+            | Operation::Rect { pos, fill, .. }
+            | Operation::Oval { pos, fill, .. } => {
+                shift(pos, dx, dy);
+                fill.translate(dx, dy);
+            }
+            Operation::Polygon { points, fill } => {
+                points.iter_mut().for_each(|point| shift(point, dx, dy));
+                fill.translate(dx, dy);
+            }
+        }
+    }
 }
 
 pub trait Drawable {
@@ -130,15 +454,44 @@ pub trait Drawable {
 }
 
 impl Drawable for Operation {
-    /// Apply operation to a [Canvas].
+    /// Apply operation to a [Canvas].\
+    /// With the `parallel` feature, large rectangles, images and canvases are rendered in parallel bands of rows.
     #[inline]
     fn apply(&self, canvas: &mut Canvas) {
+        #[cfg(feature = "parallel")]
+        if self.is_large_fill() {
+            canvas.par_raster(|raster| self.rasterize(raster));
+            return;
+        }
+
+        self.rasterize(&mut canvas.raster());
+    }
+}
+
+impl Operation {
+    /// Returns `true` if the operation is a rectangle, image or canvas worth splitting across threads.
+    #[cfg(feature = "parallel")]
+    #[inline]
+    fn is_large_fill(&self) -> bool {
+        match self {
+            Operation::Rect { width, height, .. }
+            | Operation::Image { width, height, .. }
+            | Operation::Canvas { width, height, .. } => {
+                *width as usize * *height as usize >= PARALLEL_MIN_PIXELS
+            }
+            _ => false,
+        }
+    }
+
+    /// Rasterize the operation into the rows of `raster`.
+    #[inline]
+    pub(crate) fn rasterize(&self, canvas: &mut Raster) {
         match self {
             Operation::Oval {
                 pos,
                 width,
                 height,
-                color,
+                fill,
             } => {
                 let start_x = pos.x;
                 let start_y = pos.y;
@@ -150,29 +503,38 @@ impl Drawable for Operation {
                 let cx = start_x as f32 + a;
                 let cy = start_y as f32 + b;
 
-                for y in start_y..end_y {
-                    for x in start_x..end_x {
-                        let px = x as f32 + 0.5;
-                        let py = y as f32 + 0.5;
-                        let dx = px - cx;
-                        let dy = py - cy;
+                for y in canvas.clip_rows(start_y as usize, end_y as usize) {
+                    // half width of the oval at the center of this row
+                    let dy = y as f32 + 0.5 - cy;
+                    let t = 1.0 - dy * dy / (b * b);
+                    if t < 0.0 {
+                        continue;
+                    }
+                    let half = a * t.sqrt();
 
-                        let distance_squared = (dx * dx / (a * a) + dy * dy / (b * b)).abs();
+                    // pixels whose center is inside of the oval
+                    let from = (cx - half - 0.5).ceil().max(start_x as f32) as usize;
+                    let to = ((cx + half - 0.5).floor() + 1.0).min(end_x as f32).max(0.0) as usize;
 
-                        if distance_squared <= 1.0 {
-                            canvas.set_pixel(x as usize, y as usize, color);
-                        }
-                    }
+                    canvas.fill_span(y, from, to, fill);
                 }
             }
 
             Operation::Line { to, from, color } => {
+                // skip lines outside of the rows
+                let rows = canvas.rows();
+                if (from.y.min(to.y) as usize) >= rows.end
+                    || (from.y.max(to.y) as usize) < rows.start
+                {
+                    return;
+                }
+
                 let dx = to.x as isize - from.x as isize;
                 let dy = to.y as isize - from.y as isize;
-                let steps = usize::max(dx.abs() as usize, dy.abs() as usize);
+                let steps = usize::max(dx.unsigned_abs(), dy.unsigned_abs());
 
                 if steps == 0 {
-                    canvas.set_pixel(from.x as usize, from.y as usize, color);
+                    canvas.blend_pixel(from.x as usize, from.y as usize, color);
                     return;
                 }
 
@@ -183,48 +545,29 @@ impl Drawable for Operation {
                 let mut y = from.y as f32;
 
                 for _ in 0..=steps {
-                    canvas.set_pixel(x as usize, y as usize, color);
+                    canvas.blend_pixel(x as usize, y as usize, color);
                     x += x_increment;
                     y += y_increment;
                 }
             }
 
-            Operation::Circle { pos, radius, color } => {
-                let radius_squared = (*radius as f32).powi(2);
-
-                let (cx, cy) = (pos.x as isize, pos.y as isize);
-                let (start_x, start_y) = (cx - *radius as isize, cy - *radius as isize);
-                let (end_x, end_y) = (cx + *radius as isize, cy + *radius as isize);
-
-                for y in start_y..=end_y {
-                    for x in start_x..=end_x {
-                        let dx = x - cx;
-                        let dy = y - cy;
-                        let distance_squared = (dx * dx + dy * dy) as f32;
-
-                        if distance_squared <= radius_squared {
-                            canvas.set_pixel(x as usize, y as usize, color);
-                        }
-                    }
-                }
+            Operation::Circle { pos, radius, fill } => {
+                canvas.fill_circle(pos.x as i64, pos.y as i64, *radius as i64, fill);
             }
 
             Operation::Rect {
                 pos,
                 width,
                 height,
-                color,
+                fill,
             } => {
-                let start_x = pos.x as usize;
-                let start_y = pos.y as usize;
-                let end_x = start_x + *width as usize;
-                let end_y = start_y + *height as usize;
-
-                for y in start_y..end_y {
-                    for x in start_x..end_x {
-                        canvas.set_pixel(x, y, color);
-                    }
-                }
+                canvas.fill_rect(
+                    pos.x as i64,
+                    pos.y as i64,
+                    *width as i64,
+                    *height as i64,
+                    fill,
+                );
             }
 
             Operation::Image {
@@ -233,20 +576,394 @@ impl Drawable for Operation {
                 height,
                 data,
             } => {
+                // skip images with less data than pixels
+                if data.len() < *width as usize * *height as usize {
+                    return;
+                }
+
                 let start_x = pos.x as usize;
                 let start_y = pos.y as usize;
                 let end_x = start_x + *width as usize;
                 let end_y = start_y + *height as usize;
 
-                let mut index = 0;
+                for y in canvas.clip_rows(start_y, end_y) {
+                    let row = (y - start_y) * *width as usize;
+                    canvas.blend_span(y, start_x, &data[row..row + end_x - start_x]);
+                }
+            }
+
+            Operation::Canvas {
+                pos,
+                width,
+                height,
+                source,
+                opacity,
+            } => blit(canvas, source, *pos, *width, *height, *opacity),
+
+            Operation::Polygon { points, fill } => {
+                if points.len() < 3 {
+                    return;
+                }
+
+                let min_y = points.iter().map(|p| p.y).min().unwrap_or(0);
+                let max_y = points.iter().map(|p| p.y).max().unwrap_or(0);
+
+                let mut crossings: Vec<f32> = Vec::with_capacity(points.len());
+
+                for y in canvas.clip_rows(min_y as usize, max_y as usize) {
+                    let y = y as u32;
+                    // sample at the pixel center
+                    let py = y as f32 + 0.5;
 
-                for y in start_y..end_y {
-                    for x in start_x..end_x {
-                        canvas.set_pixel(x, y, &data[index]);
-                        index += 1;
+                    // collect x coordinates where the edges cross this row
+                    crossings.clear();
+                    for (i, a) in points.iter().enumerate() {
+                        let b = &points[(i + 1) % points.len()];
+                        let (ay, by) = (a.y as f32, b.y as f32);
+
+                        if (ay <= py) != (by <= py) {
+                            let t = (py - ay) / (by - ay);
+                            crossings.push(a.x as f32 + t * (b.x as f32 - a.x as f32));
+                        }
+                    }
+                    crossings.sort_by(|a, b| a.total_cmp(b));
+
+                    // fill between pairs of crossings (even-odd rule)
+                    for span in crossings.chunks_exact(2) {
+                        let start_x = (span[0] - 0.5).ceil().max(0.0) as usize;
+                        let end_x = (span[1] - 0.5).ceil().max(0.0) as usize;
+
+                        canvas.fill_span(y as usize, start_x, end_x, fill);
                     }
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::render::canvas::Canvas;
+    use crate::render::color::Color;
+    use crate::render::fill::{Fill, LinearGradient, RadialGradient};
+    use crate::render::sketch::{Operation, Sketch};
+    use crate::simplified::vec2;
+    use std::sync::Arc;
+
+    fn gradient() -> LinearGradient {
+        LinearGradient::new(vec2(10, 0), vec2(20, 0))
+            .with_stop(0.0, Color::RED)
+            .with_stop(1.0, Color::BLUE)
+    }
+
+    #[test]
+    fn hands_out_handles_in_order() {
+        let mut sketch = Sketch::new();
+        assert_eq!(sketch.last_handle(), None);
+
+        let line = sketch
+            .line(vec2(0, 0), vec2(4, 4), Color::RED)
+            .last_handle()
+            .unwrap();
+        let rect = sketch
+            .rect(vec2(1, 1), 2, 2, Color::GREEN)
+            .last_handle()
+            .unwrap();
+
+        assert!(line < rect);
+        assert_eq!(sketch.len(), 2);
+        assert!(matches!(sketch.get(line), Some(Operation::Line { .. })));
+        assert!(matches!(sketch.get(rect), Some(Operation::Rect { .. })));
+
+        if let Some(Operation::Rect { width, .. }) = sketch.get_mut(rect) {
+            *width = 7;
+        }
+        assert!(matches!(
+            sketch.get(rect),
+            Some(Operation::Rect { width: 7, .. })
+        ));
+    }
+
+    #[test]
+    fn translates_operations_and_gradients() {
+        let mut sketch = Sketch::new();
+        let rect = sketch
+            .rect(vec2(10, 10), 5, 5, gradient())
+            .last_handle()
+            .unwrap();
+        let line = sketch
+            .line(vec2(2, 8), vec2(6, 1), Color::RED)
+            .last_handle()
+            .unwrap();
+
+        sketch.translate(rect, 5, -20).unwrap();
+        assert_eq!(
+            sketch.get(rect),
+            Some(&Operation::Rect {
+                pos: vec2(15, 0),
+                width: 5,
+                height: 5,
+                fill: LinearGradient::new(vec2(15, 0), vec2(25, 0))
+                    .with_stop(0.0, Color::RED)
+                    .with_stop(1.0, Color::BLUE)
+                    .into(),
+            })
+        );
+
+        // coordinates saturate at 0
+        sketch.translate(line, -4, -4).unwrap();
+        assert_eq!(
+            sketch.get(line),
+            Some(&Operation::Line {
+                from: vec2(0, 4),
+                to: vec2(2, 0),
+                color: Color::RED,
+            })
+        );
+    }
+
+    #[test]
+    fn sets_fills_of_fillable_operations() {
+        let mut sketch = Sketch::new();
+        let line = sketch
+            .line(vec2(0, 0), vec2(4, 4), Color::RED)
+            .last_handle()
+            .unwrap();
+        let circle = sketch
+            .circle(vec2(4, 4), 2, Color::RED)
+            .last_handle()
+            .unwrap();
+        let image = sketch
+            .image(vec2(0, 0), 1, 1, vec![Color::RED])
+            .last_handle()
+            .unwrap();
+
+        assert_eq!(sketch.set_fill(line, Color::BLUE), Some(()));
+        assert_eq!(sketch.set_fill(line, gradient()), None);
+        assert!(matches!(
+            sketch.get(line),
+            Some(Operation::Line {
+                color: Color::BLUE,
+                ..
+            })
+        ));
+
+        let radial = RadialGradient::new(vec2(4, 4), 2).with_stop(0.0, Color::WHITE);
+        assert_eq!(sketch.set_fill(circle, radial.clone()), Some(()));
+        assert!(
+            matches!(sketch.get(circle), Some(Operation::Circle { fill, .. }) if *fill == Fill::Radial(radial))
+        );
+
+        assert_eq!(sketch.set_fill(image, Color::BLUE), None);
+    }
+
+    #[test]
+    fn toggles_visibility() {
+        let mut sketch = Sketch::new();
+        let first = sketch
+            .rect(vec2(0, 0), 2, 2, Color::RED)
+            .last_handle()
+            .unwrap();
+        sketch.rect(vec2(2, 0), 2, 2, Color::BLUE);
+
+        assert_eq!(sketch.is_visible(first), Some(true));
+        sketch.set_visible(first, false).unwrap();
+        assert_eq!(sketch.is_visible(first), Some(false));
+        assert_eq!(sketch.visible_operations().count(), 1);
+
+        let mut canvas = Canvas::new(4, 2);
+        canvas.draw(&sketch);
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::BLACK));
+        assert_eq!(canvas.get_pixel(3, 0), Some(Color::BLUE));
+    }
+
+    #[test]
+    fn skips_images_with_short_data() {
+        let mut canvas = Canvas::new(8, 8);
+        canvas.draw(
+            Sketch::new()
+                .image(vec2(0, 0), 4, 4, vec![Color::RED; 3])
+                .image(vec2(4, 4), 2, 2, vec![Color::BLUE; 4]),
+        );
+
+        assert_eq!(canvas.get_pixel(0, 0), Some(Color::BLACK));
+        assert_eq!(canvas.get_pixel(5, 5), Some(Color::BLUE));
+    }
+
+    #[test]
+    fn compares_canvases_by_identity() {
+        let source = Arc::new(Canvas::new(2, 2));
+        let operation = |source| {
+            Operation::Canvas {
+                pos: vec2(0, 0),
+                width: 2,
+                height: 2,
+                source,
+                opacity: 255,
+            }
+        };
+
+        assert_eq!(operation(source.clone()), operation(source.clone()));
+        assert_ne!(operation(source), operation(Arc::new(Canvas::new(2, 2))));
+    }
+
+    #[test]
+    fn clear_invalidates_handles_and_keeps_capacity() {
+        let mut sketch = Sketch::with_capacity(8);
+        let handle = sketch
+            .circle(vec2(1, 1), 1, Color::RED)
+            .last_handle()
+            .unwrap();
+        sketch.clear();
+
+        assert!(sketch.is_empty());
+        assert!(sketch.operations.capacity() >= 8 && sketch.visible.capacity() >= 8);
+        assert_eq!(sketch.get(handle), None);
+        assert_eq!(sketch.translate(handle, 1, 1), None);
+        assert_eq!(sketch.set_fill(handle, Color::BLUE), None);
+        assert_eq!(sketch.set_visible(handle, false), None);
+        assert_eq!(sketch.is_visible(handle), None);
+    }
+
+    #[test]
+    fn fills_polygons_even_odd() {
+        let mut canvas = Canvas::new(100, 100);
+        canvas.cleanse();
+
+        // a pentagram, whose center is enclosed twice
+        canvas.draw(Sketch::new().polygon(
+            vec![
+                vec2(50, 5),
+                vec2(76, 86),
+                vec2(7, 36),
+                vec2(93, 36),
+                vec2(24, 86),
+            ],
+            Color::RED,
+        ));
+
+        assert_eq!(canvas.get_pixel(50, 20), Some(Color::RED));
+        assert_eq!(canvas.get_pixel(15, 38), Some(Color::RED));
+        assert_eq!(canvas.get_pixel(50, 50), Some(Color::BLACK));
+        assert_eq!(canvas.get_pixel(5, 90), Some(Color::BLACK));
+    }
+
+    #[test]
+    fn skips_degenerate_polygons() {
+        let mut canvas = Canvas::new(10, 10);
+        canvas.cleanse();
+        canvas.draw(
+            Sketch::new()
+                .polygon(vec![vec2(0, 0), vec2(9, 9)], Color::RED)
+                .polygon(vec![vec2(0, 5), vec2(5, 5), vec2(9, 5)], Color::RED),
+        );
+
+        assert!(canvas
+            .get_buffer()
+            .as_slice()
+            .iter()
+            .all(|px| *px == Color::BLACK.0));
+    }
+}
+
+#[cfg(all(test, feature = "parallel"))]
+mod parallel_tests {
+    use super::*;
+    use crate::render::fill::{LinearGradient, RadialGradient};
+    use crate::simplified::vec2;
+
+    /// Build a sketch containing every kind of operation, partially outside of the canvas.
+    fn sketch() -> Sketch {
+        let image: Vec<Color> = (0..40 * 30)
+            .map(|i| Color::from_rgba(i as u8, (i / 3) as u8, 200, (i % 256) as u8))
+            .collect();
+
+        let mut sketch = Sketch::new();
+        sketch
+            .rect(
+                vec2(0, 0),
+                203,
+                157,
+                LinearGradient::new(vec2(0, 0), vec2(0, 157))
+                    .with_stop(0.0, Color::BLUE)
+                    .with_stop(1.0, Color::WHITE),
+            )
+            .line(vec2(3, 150), vec2(200, 2), Color::RED)
+            .line(vec2(10, 10), vec2(10, 400), Color::YELLOW)
+            .circle(vec2(40, 40), 35, Color::GREEN.with_alpha(128))
+            .circle(
+                vec2(180, 140),
+                50,
+                RadialGradient::new(vec2(180, 140), 50)
+                    .with_stop(0.0, Color::WHITE)
+                    .with_stop(1.0, Color::TRANSPARENT),
+            )
+            .oval(vec2(60, 80), 120, 60, Color::CYAN)
+            .image(vec2(150, 5), 40, 30, image)
+            .canvas(vec2(120, 90), 90, 45, target(), 160)
+            .polygon(
+                vec![vec2(100, 10), vec2(190, 120), vec2(20, 140)],
+                Color::MAGENTA.with_alpha(100),
+            );
+        sketch
+    }
+
+    /// Build an off-screen canvas with a translucent shape.
+    fn target() -> Canvas {
+        let mut target = Canvas::new(30, 15);
+        target.draw(
+            Sketch::new()
+                .rect(vec2(0, 0), 30, 15, Color::RED.with_alpha(200))
+                .circle(vec2(15, 7), 6, Color::BLUE),
+        );
+        target
+    }
+
+    /// Render `sketch` without splitting the canvas.
+    fn render_serial(sketch: &Sketch, canvas: &mut Canvas) {
+        let mut raster = canvas.raster();
+        for op in sketch.visible_operations() {
+            op.rasterize(&mut raster);
+        }
+    }
+
+    #[test]
+    fn parallel_sketch_matches_serial() {
+        let sketch = sketch();
+
+        let mut serial = Canvas::new(203, 157);
+        render_serial(&sketch, &mut serial);
+
+        let mut parallel = Canvas::new(203, 157);
+        parallel.draw(&sketch);
+
+        assert_eq!(serial.buffer().as_slice(), parallel.buffer().as_slice());
+    }
+
+    #[test]
+    fn parallel_operations_match_serial() {
+        for op in sketch().get_operations() {
+            let mut serial = Canvas::new(203, 157);
+            serial.cleanse();
+            op.rasterize(&mut serial.raster());
+
+            let mut parallel = Canvas::new(203, 157);
+            parallel.cleanse();
+            parallel.drawable(op);
+
+            assert_eq!(serial.buffer().as_slice(), parallel.buffer().as_slice());
+        }
+    }
+
+    #[test]
+    fn parallel_clear_fills_every_pixel() {
+        let mut canvas = Canvas::new(203, 157);
+        canvas.clear(&Color::ORANGE);
+
+        assert!(canvas
+            .buffer()
+            .as_slice()
+            .iter()
+            .all(|px| *px == Color::ORANGE.0));
+    }
+}
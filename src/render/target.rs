@@ -0,0 +1,101 @@
+use crate::render::canvas::Canvas;
+use crate::render::color::Color;
+use crate::render::raster::Raster;
+use crate::render::sketch::Drawable;
+use mint::Vector2;
+
+/// Borrowed view of an off-screen [Canvas], which can be drawn onto another canvas like an image.
+///
+/// The source pixels are read directly, so nothing gets copied before drawing.
+/// Use [Canvas::as_image] to create one.\
+/// To keep an off-screen canvas in a [crate::render::sketch::Sketch], use [crate::render::sketch::Sketch::canvas].
+#[derive(Clone, Copy, Debug)]
+pub struct CanvasImage<'a> {
+    source: &'a Canvas,
+    pos: Vector2<u32>,
+    width: u32,
+    height: u32,
+    opacity: u8,
+}
+
+impl<'a> CanvasImage<'a> {
+    /// Create a view of `source` at `pos` with its original size.
+    #[inline]
+    pub fn new(source: &'a Canvas, pos: Vector2<u32>) -> Self {
+        Self {
+            source,
+            pos,
+            width: source.width() as u32,
+            height: source.height() as u32,
+            opacity: 255,
+        }
+    }
+
+    /// Scale the source to `width` and `height` with nearest-neighbor sampling.
+    #[inline]
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Multiply the alpha of every source pixel with `opacity`, where `255` keeps the source alpha.
+    #[inline]
+    pub fn with_opacity(mut self, opacity: u8) -> Self {
+        self.opacity = opacity;
+        self
+    }
+}
+
+impl Drawable for CanvasImage<'_> {
+    /// Blend the source canvas on top of `canvas`.
+    #[inline]
+    fn apply(&self, canvas: &mut Canvas) {
+        blit(
+            &mut canvas.raster(),
+            self.source,
+            self.pos,
+            self.width,
+            self.height,
+            self.opacity,
+        );
+    }
+}
+
+/// Blend `source` scaled to `width` and `height` at `pos` into `raster`.
+#[inline]
+pub(crate) fn blit(
+    raster: &mut Raster,
+    source: &Canvas,
+    pos: Vector2<u32>,
+    width: u32,
+    height: u32,
+    opacity: u8,
+) {
+    let (src_width, src_height) = (source.width(), source.height());
+    if src_width == 0 || src_height == 0 || width == 0 || height == 0 || opacity == 0 {
+        return;
+    }
+
+    let pixels = source.get_buffer().as_slice();
+    let (start_x, start_y) = (pos.x as usize, pos.y as usize);
+    let (width, height) = (width as usize, height as usize);
+    let mut row = Vec::with_capacity(width);
+
+    for y in raster.clip_rows(start_y, start_y + height) {
+        let sy = (y - start_y) * src_height / height;
+        let src_row = &pixels[sy * src_width..(sy + 1) * src_width];
+
+        row.clear();
+        row.extend((0..width).map(|x| {
+            let color = Color(src_row[x * src_width / width]);
+            if opacity == 255 {
+                color
+            } else {
+                color.with_alpha((color.alpha() as u32 * opacity as u32 / 255) as u8)
+            }
+        }));
+
+        raster.blend_span(y, start_x, &row);
+    }
+}
@@ -0,0 +1,267 @@
+use crate::render::canvas::Canvas;
+use crate::render::color::Color;
+use crate::render::damage::DirtyRect;
+
+/// How a logical canvas gets scaled to the window.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Scaling {
+    /// Scale by the largest whole number that fits, so every pixel has the same size.
+    #[default]
+    Integer,
+    /// Scale by the largest factor that fits, keeping the aspect ratio.
+    Fractional,
+}
+
+/// A fixed canvas size, which the game scales to the window itself.
+///
+/// Useful for pixel-art games, which are drawn at e.g. 320x180 and shown at any window size.
+/// Unused space of the window is filled with bars of [LogicalResolution::bar_color].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LogicalResolution {
+    /// Width of the canvas.
+    pub width: usize,
+    /// Height of the canvas.
+    pub height: usize,
+    /// How the canvas gets scaled. Default is [Scaling::Integer].
+    pub scaling: Scaling,
+    /// Color of the bars around the canvas. Default is [Color::BLACK].
+    pub bar_color: Color,
+}
+
+impl LogicalResolution {
+    /// Create a new logical resolution with integer scaling and black bars.
+    #[inline]
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            scaling: Scaling::Integer,
+            bar_color: Color::BLACK,
+        }
+    }
+
+    /// Use `scaling` to scale the canvas.
+    #[inline]
+    pub fn with_scaling(mut self, scaling: Scaling) -> Self {
+        self.scaling = scaling;
+        self
+    }
+
+    /// Use `color` for the bars around the canvas.
+    #[inline]
+    pub fn with_bar_color(mut self, color: Color) -> Self {
+        self.bar_color = color;
+        self
+    }
+}
+
+/// The area of the window, which shows the logical canvas.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    /// Left edge of the area in window pixels.
+    pub x: usize,
+    /// Top edge of the area in window pixels.
+    pub y: usize,
+    /// Width of the area in window pixels.
+    pub width: usize,
+    /// Height of the area in window pixels.
+    pub height: usize,
+    /// Size of one canvas pixel in window pixels.
+    pub scale: f32,
+    /// Logical resolution shown in the area.
+    pub logical: LogicalResolution,
+}
+
+impl Viewport {
+    /// Fit `logical` into a window of `window_width` and `window_height`, centered with bars around it.\
+    /// With [Scaling::Integer], the scale is at least `1`, even if the canvas doesn't fit.
+    #[inline]
+    pub fn new(window_width: usize, window_height: usize, logical: LogicalResolution) -> Self {
+        let (lw, lh) = (logical.width.max(1) as f32, logical.height.max(1) as f32);
+        let fit = (window_width as f32 / lw).min(window_height as f32 / lh);
+
+        let scale = match logical.scaling {
+            Scaling::Integer => fit.floor().max(1.0),
+            Scaling::Fractional => fit.max(f32::EPSILON),
+        };
+
+        let width = (lw * scale).round() as usize;
+        let height = (lh * scale).round() as usize;
+
+        Self {
+            x: window_width.saturating_sub(width) / 2,
+            y: window_height.saturating_sub(height) / 2,
+            width,
+            height,
+            scale,
+            logical,
+        }
+    }
+
+    /// Map a position in the window to the logical canvas.\
+    /// The result is outside of the canvas, if the position is on the bars.
+    #[inline]
+    pub fn to_logical(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            (x - self.x as f32) / self.scale,
+            (y - self.y as f32) / self.scale,
+        )
+    }
+
+    /// Map a position on the logical canvas to the window.
+    #[inline]
+    pub fn to_window(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            x * self.scale + self.x as f32,
+            y * self.scale + self.y as f32,
+        )
+    }
+
+    /// Scale `frame` into `screen`, a buffer of the window with `screen_width`, and draw the bars.
+    #[inline]
+    pub(crate) fn present(&self, frame: &Canvas, screen: &mut [u32], screen_width: usize) {
+        screen.fill(self.logical.bar_color.0);
+
+        let rect = DirtyRect {
+            x: 0,
+            y: 0,
+            width: frame.width(),
+            height: frame.height(),
+        };
+        self.present_rect(frame, screen, screen_width, rect);
+    }
+
+    /// Scale only the `rects` of `frame` into `screen`, which already contains the previous frame
+    /// presented with this viewport.
+    #[inline]
+    pub(crate) fn present_rects(
+        &self,
+        frame: &Canvas,
+        screen: &mut [u32],
+        screen_width: usize,
+        rects: &[DirtyRect],
+    ) {
+        for rect in rects {
+            self.present_rect(frame, screen, screen_width, *rect);
+        }
+    }
+
+    /// Scale the `rect` of `frame` to the window pixels covering it.
+    fn present_rect(
+        &self,
+        frame: &Canvas,
+        screen: &mut [u32],
+        screen_width: usize,
+        rect: DirtyRect,
+    ) {
+        let (src_width, src_height) = (frame.width(), frame.height());
+        if screen_width == 0 || src_width == 0 || src_height == 0 {
+            return;
+        }
+
+        let pixels = frame.get_buffer().as_slice();
+        let screen_height = screen.len() / screen_width;
+
+        // first target pixel, whose source pixel is at or after `src`
+        let target = |src: usize, size: usize, src_size: usize| (src * size).div_ceil(src_size);
+
+        // source column of every visible target column
+        let start_x = self.x + target(rect.x, self.width, src_width);
+        let end_x = (self.x + target(rect.x + rect.width, self.width, src_width)).min(screen_width);
+        let columns: Vec<usize> = (start_x..end_x)
+            .map(|x| (x - self.x) * src_width / self.width)
+            .collect();
+
+        let start_y = self.y + target(rect.y, self.height, src_height);
+        let end_y =
+            (self.y + target(rect.y + rect.height, self.height, src_height)).min(screen_height);
+        for y in start_y..end_y {
+            let sy = (y - self.y) * src_height / self.height;
+            let src_row = &pixels[sy * src_width..(sy + 1) * src_width];
+            let row = &mut screen[y * screen_width + start_x..y * screen_width + end_x];
+
+            for (px, sx) in row.iter_mut().zip(&columns) {
+                *px = src_row[*sx];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::canvas::Canvas;
+    use crate::render::color::Color;
+    use crate::render::damage::DirtyRect;
+    use crate::render::viewport::{LogicalResolution, Scaling, Viewport};
+
+    #[test]
+    fn integer_scaling_letterboxes() {
+        let viewport = Viewport::new(1000, 600, LogicalResolution::new(320, 180));
+
+        assert_eq!(viewport.scale, 3.0);
+        assert_eq!((viewport.x, viewport.y), (20, 30));
+        assert_eq!((viewport.width, viewport.height), (960, 540));
+        assert_eq!(
+            viewport.to_logical(20.0 + 3.0 * 10.0, 30.0 + 3.0 * 5.0),
+            (10.0, 5.0)
+        );
+        assert_eq!(viewport.to_window(10.0, 5.0), (50.0, 45.0));
+    }
+
+    #[test]
+    fn fractional_scaling_fills_one_axis() {
+        let logical = LogicalResolution::new(320, 180).with_scaling(Scaling::Fractional);
+        let viewport = Viewport::new(1000, 600, logical);
+
+        assert_eq!(viewport.scale, 3.125);
+        assert_eq!((viewport.x, viewport.y), (0, 18));
+        assert_eq!((viewport.width, viewport.height), (1000, 563));
+    }
+
+    #[test]
+    fn presents_with_bars() {
+        let mut frame = Canvas::new(2, 1);
+        frame.set_pixel(0, 0, &Color::RED);
+        frame.set_pixel(1, 0, &Color::GREEN);
+
+        let logical = LogicalResolution::new(2, 1).with_bar_color(Color::BLUE);
+        let viewport = Viewport::new(6, 5, logical);
+        let mut screen = vec![0; 6 * 5];
+        viewport.present(&frame, &mut screen, 6);
+
+        let (r, g, b) = (Color::RED.0, Color::GREEN.0, Color::BLUE.0);
+        #[rustfmt::skip]
+        assert_eq!(screen, vec![
+            b, b, b, b, b, b,
+            r, r, r, g, g, g,
+            r, r, r, g, g, g,
+            r, r, r, g, g, g,
+            b, b, b, b, b, b,
+        ]);
+    }
+
+    #[test]
+    fn presents_only_dirty_rects() {
+        let mut frame = Canvas::new(2, 2);
+        frame.clear(&Color::RED);
+
+        let viewport = Viewport::new(4, 4, LogicalResolution::new(2, 2));
+        let mut screen = vec![0; 4 * 4];
+        let rect = DirtyRect {
+            x: 1,
+            y: 0,
+            width: 1,
+            height: 1,
+        };
+        viewport.present_rects(&frame, &mut screen, 4, &[rect]);
+
+        let r = Color::RED.0;
+        #[rustfmt::skip]
+        assert_eq!(screen, vec![
+            0, 0, r, r,
+            0, 0, r, r,
+            0, 0, 0, 0,
+            0, 0, 0, 0,
+        ]);
+    }
+}
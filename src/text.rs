@@ -1,32 +1,311 @@
 use crate::render::canvas::Canvas;
 use crate::render::color::Color;
+use crate::render::raster::Raster;
 use crate::render::sketch::Drawable;
-use fontdue::layout::{CoordinateSystem, Layout, TextStyle};
-use fontdue::Font;
+use crate::text::effects::{GlyphState, TextAnimation};
+use fontdue::layout::{
+    CoordinateSystem, GlyphPosition, GlyphRasterConfig, Layout, LayoutSettings, TextStyle,
+};
 use mint::Vector2;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+use std::time::Duration;
+
+/// Contains the [effects::TextAnimation] enum for per-character animations.
+pub mod effects;
+mod markup;
+
+/// Loaded font with a cache of rasterized glyphs, which can be shared between [TextSketch]es.
+///
+/// Parsing a font is expensive, so load it once and clone the handle instead.
+/// Clones share the font data and the glyph cache, so every glyph gets rasterized only once per size.
+#[derive(Clone)]
+pub struct Font {
+    inner: Arc<FontInner>,
+}
+
+struct FontInner {
+    font: fontdue::Font,
+    cache: Mutex<GlyphCache>,
+}
+
+/// Rasterized glyphs of a [Font], keyed by glyph, size and whether they were rasterized with subpixels.\
+/// Outlines are keyed by glyph, size and outline width.
+#[derive(Default)]
+struct GlyphCache {
+    glyphs: HashMap<(GlyphRasterConfig, bool), Arc<RasterGlyph>>,
+    outlines: HashMap<(GlyphRasterConfig, usize), Arc<RasterGlyph>>,
+    hits: u64,
+    misses: u64,
+}
+
+/// Coverage of a rasterized glyph. Subpixel glyphs have three values per pixel.
+struct RasterGlyph {
+    width: usize,
+    height: usize,
+    coverage: Vec<u8>,
+}
+
+/// Usage statistics of the glyph cache of a [Font].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct CacheStats {
+    /// Number of cached glyphs, including outlines.
+    pub glyphs: usize,
+    /// Number of glyphs drawn from the cache.
+    pub hits: u64,
+    /// Number of glyphs, which had to be rasterized.
+    pub misses: u64,
+}
+
+impl Font {
+    /// Load a font from TTF or OTF `data`.\
+    /// Returns [None] if the data is not a valid font.
+    #[inline]
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let font = fontdue::Font::from_bytes(data, Default::default()).ok()?;
+
+        Some(Self {
+            inner: Arc::new(FontInner {
+                font,
+                cache: Mutex::new(GlyphCache::default()),
+            }),
+        })
+    }
+
+    /// Get the underlying [fontdue::Font].
+    #[inline]
+    pub fn as_fontdue(&self) -> &fontdue::Font { &self.inner.font }
+
+    /// Get the statistics of the glyph cache since the font was loaded or [Font::clear_cache] was called.\
+    /// Compare them between frames to see how many glyphs get rasterized per frame.
+    #[inline]
+    pub fn cache_stats(&self) -> CacheStats {
+        let cache = self.cache();
+        CacheStats {
+            glyphs: cache.glyphs.len() + cache.outlines.len(),
+            hits: cache.hits,
+            misses: cache.misses,
+        }
+    }
+
+    /// Remove all cached glyphs and reset the statistics.
+    #[inline]
+    pub fn clear_cache(&self) { *self.cache() = GlyphCache::default(); }
+
+    #[inline]
+    fn cache(&self) -> MutexGuard<'_, GlyphCache> {
+        self.inner
+            .cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get the rasterized glyph for `key` from the cache or rasterize it.
+    fn glyph(&self, key: GlyphRasterConfig, subpixel: bool) -> Arc<RasterGlyph> {
+        let mut cache = self.cache();
+        if let Some(glyph) = cache.glyphs.get(&(key, subpixel)) {
+            let glyph = glyph.clone();
+            cache.hits += 1;
+            return glyph;
+        }
+
+        let (metrics, coverage) = if subpixel {
+            self.inner.font.rasterize_config_subpixel(key)
+        } else {
+            self.inner.font.rasterize_config(key)
+        };
+        let glyph = Arc::new(RasterGlyph {
+            width: metrics.width,
+            height: metrics.height,
+            coverage,
+        });
+
+        cache.misses += 1;
+        cache.glyphs.insert((key, subpixel), glyph.clone());
+        glyph
+    }
+
+    /// Get the grayscale glyph for `key` grown by `radius` pixels from the cache or rasterize it.
+    fn outline(&self, key: GlyphRasterConfig, radius: usize) -> Arc<RasterGlyph> {
+        {
+            let mut cache = self.cache();
+            if let Some(outline) = cache.outlines.get(&(key, radius)) {
+                let outline = outline.clone();
+                cache.hits += 1;
+                return outline;
+            }
+        }
+
+        // the glyph lookup locks the cache itself
+        let glyph = self.glyph(key, false);
+        let outline = Arc::new(RasterGlyph {
+            width: glyph.width + 2 * radius,
+            height: glyph.height + 2 * radius,
+            coverage: effects::dilate(&glyph.coverage, glyph.width, glyph.height, radius),
+        });
+
+        self.cache().outlines.insert((key, radius), outline.clone());
+        outline
+    }
+}
+
+impl Borrow<fontdue::Font> for Font {
+    #[inline]
+    fn borrow(&self) -> &fontdue::Font { self.as_fontdue() }
+}
+
+/// Part of a text with its own size, font and optional color.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextSpan {
+    text: String,
+    px: f32,
+    color: Option<Color>,
+    font: usize,
+}
+
+impl TextSpan {
+    /// Create a new span with `text` and `px` as size in pixels, drawn with the first font and color of the sketch.
+    #[inline]
+    pub fn new(text: impl Into<String>, px: f32) -> Self {
+        Self {
+            text: text.into(),
+            px,
+            color: None,
+            font: 0,
+        }
+    }
+
+    /// Draw the span with `color` instead of the color of the sketch.
+    #[inline]
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Set the size in pixels.
+    #[inline]
+    pub fn with_size(mut self, px: f32) -> Self {
+        self.px = px;
+        self
+    }
+
+    /// Draw the span with the font at `index` of the sketch. See [TextSketch::with_extra_font].
+    #[inline]
+    pub fn with_font(mut self, index: usize) -> Self {
+        self.font = index;
+        self
+    }
+
+    /// Get the text of the span.
+    #[inline]
+    pub fn text(&self) -> &str { &self.text }
+}
+
+/// Horizontal alignment of text lines within the width set by [TextSketch::with_max_width].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum HorizontalAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Vertical alignment of text within the height set by [TextSketch::with_bounds].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum VerticalAlign {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// Area covered by the glyphs of a [TextSketch] in canvas pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TextBounds {
+    /// Left edge of the leftmost glyph.
+    pub x: f32,
+    /// Top edge of the highest glyph.
+    pub y: f32,
+    /// Distance from the left edge of the leftmost glyph to the right edge of the rightmost glyph.
+    pub width: f32,
+    /// Distance from the top edge of the highest glyph to the bottom edge of the lowest glyph.
+    pub height: f32,
+    /// Number of lines after wrapping.
+    pub lines: usize,
+}
 
 /// Sketch to draw text on a canvas with optional [Subpixel Anti-Aliasing](https://en.wikipedia.org/wiki/Subpixel_rendering) (enabled by default).
+///
+/// Text is made of [TextSpan]s, which can have their own color, size and font.
 pub struct TextSketch {
-    layout: Layout,
-    font: Font,
+    layout: Layout<Option<Color>>,
+    fonts: Vec<Font>,
+    bold: usize,
+    italic: usize,
     color: Color,
     pos: Vector2<u32>,
     saa: bool,
+    settings: LayoutSettings,
+    spans: Vec<TextSpan>,
+    outline: Option<(Color, u32)>,
+    shadow: Option<(Color, Vector2<i32>)>,
+    animations: Vec<TextAnimation>,
+    time: f32,
+    gamma: Arc<Gamma>,
 }
 
 impl TextSketch {
-    /// Create a new empty [TextSketch] with font data.
+    /// Create a new empty [TextSketch] with font data.\
+    /// This parses the font every time, so prefer [TextSketch::with_font] when drawing every frame.
     #[inline]
     pub fn new(pos: Vector2<u32>, font: &[u8]) -> Self {
+        Self::with_font(pos, &Font::from_bytes(font).expect("Failed to load font"))
+    }
+
+    /// Create a new empty [TextSketch] with a loaded [Font], sharing its glyph cache.
+    #[inline]
+    pub fn with_font(pos: Vector2<u32>, font: &Font) -> Self {
         Self {
             layout: Layout::new(CoordinateSystem::PositiveYDown),
+            fonts: vec![font.clone()],
+            bold: 0,
+            italic: 0,
             color: Color::BLACK,
-            font: Font::from_bytes(font, Default::default()).expect("Failed to load font"),
             pos,
             saa: true,
+            settings: LayoutSettings::default(),
+            spans: Vec::new(),
+            outline: None,
+            shadow: None,
+            animations: Vec::new(),
+            time: 0.0,
+            gamma: Gamma::shared(1.0),
         }
     }
 
+    /// Add another font, which spans can select by its index.\
+    /// The font passed to [TextSketch::with_font] has index `0`, added fonts follow in order.
+    #[inline]
+    pub fn with_extra_font(mut self, font: &Font) -> Self {
+        self.fonts.push(font.clone());
+        self
+    }
+
+    /// Add a bold font, which is used by the `[b]` markup tag. Without it, the first font is used.
+    #[inline]
+    pub fn with_bold_font(mut self, font: &Font) -> Self {
+        self.bold = self.fonts.len();
+        self.with_extra_font(font)
+    }
+
+    /// Add an italic font, which is used by the `[i]` markup tag. Without it, the first font is used.
+    #[inline]
+    pub fn with_italic_font(mut self, font: &Font) -> Self {
+        self.italic = self.fonts.len();
+        self.with_extra_font(font)
+    }
+
     /// Set the color of the text.
     #[inline]
     pub fn with_color(mut self, color: Color) -> Self {
@@ -36,13 +315,209 @@ impl TextSketch {
 
     /// Append new text to the layout with `text` as text and `px` as size in pixels.
     #[inline]
-    pub fn with_text(mut self, text: &str, px: f32) -> Self {
-        self.layout
-            .append(&[self.font.clone()], &TextStyle::new(text, px, 0));
+    pub fn with_text(self, text: &str, px: f32) -> Self { self.with_span(TextSpan::new(text, px)) }
+
+    /// Append a span to the layout.\
+    /// If the span selects a font, which doesn't exist, the first font is used.
+    #[inline]
+    pub fn with_span(mut self, span: TextSpan) -> Self {
+        self.append(&span);
+        self.spans.push(span);
+        self
+    }
+
+    /// Append text with markup tags, using `px` as size in pixels outside of `[size]` tags.
+    ///
+    /// Supported tags are `[color=red]` or `[color=#FF0000]`, `[size=24]`, `[font=1]` for fonts
+    /// added with [TextSketch::with_extra_font], `[b]` and `[i]`, each closed by the matching `[/tag]`.
+    /// Use `[[` for a literal `[`. Unknown, invalid or unmatched closing tags are drawn as text,
+    /// unclosed tags apply to the rest of the text.
+    #[inline]
+    pub fn with_markup(mut self, markup: &str, px: f32) -> Self {
+        let fonts = markup::MarkupFonts {
+            bold: self.bold,
+            italic: self.italic,
+        };
+
+        for span in markup::parse(markup, &TextSpan::new("", px), fonts) {
+            self = self.with_span(span);
+        }
+        self
+    }
+
+    /// Wrap words, which exceed `width`, into the next line and align lines within it.
+    #[inline]
+    pub fn with_max_width(mut self, width: f32) -> Self {
+        self.settings.max_width = Some(width);
+        self.relayout();
+        self
+    }
+
+    /// Wrap and align the text within a box of `width` and `height` at the position of the sketch.\
+    /// Text exceeding the height is still drawn.
+    #[inline]
+    pub fn with_bounds(mut self, width: f32, height: f32) -> Self {
+        self.settings.max_width = Some(width);
+        self.settings.max_height = Some(height);
+        self.relayout();
+        self
+    }
+
+    /// Set the alignment within the box set by [TextSketch::with_max_width] or [TextSketch::with_bounds].\
+    /// Default is [HorizontalAlign::Left] and [VerticalAlign::Top].
+    #[inline]
+    pub fn with_align(mut self, horizontal: HorizontalAlign, vertical: VerticalAlign) -> Self {
+        self.settings.horizontal_align = match horizontal {
+            HorizontalAlign::Left => fontdue::layout::HorizontalAlign::Left,
+            HorizontalAlign::Center => fontdue::layout::HorizontalAlign::Center,
+            HorizontalAlign::Right => fontdue::layout::HorizontalAlign::Right,
+        };
+        self.settings.vertical_align = match vertical {
+            VerticalAlign::Top => fontdue::layout::VerticalAlign::Top,
+            VerticalAlign::Middle => fontdue::layout::VerticalAlign::Middle,
+            VerticalAlign::Bottom => fontdue::layout::VerticalAlign::Bottom,
+        };
+        self.relayout();
+        self
+    }
+
+    /// Multiply the height of every line with `spacing`. Default is `1.0`.
+    #[inline]
+    pub fn with_line_spacing(mut self, spacing: f32) -> Self {
+        self.settings.line_height = spacing;
+        self.relayout();
         self
     }
 
-    /// Set whether to enable [Subpixel Anti-Aliasing](https://en.wikipedia.org/wiki/Subpixel_rendering).
+    /// Blend the glyph coverage in a color space with `gamma`. Default is `1.0`, which blends linearly.\
+    /// Values around `2.2` blend in approximately linear light, so dark text on light backgrounds
+    /// looks thinner and light text on dark backgrounds bolder. `gamma` is rounded to hundredths.
+    #[inline]
+    pub fn with_gamma(mut self, gamma: f32) -> Self {
+        self.gamma = Gamma::shared(gamma);
+        self
+    }
+
+    /// Draw an outline of `width` pixels with `color` around every character.
+    #[inline]
+    pub fn with_outline(mut self, color: Color, width: u32) -> Self {
+        self.outline = Some((color, width));
+        self
+    }
+
+    /// Draw a shadow with `color` below the text, moved by `offset` pixels.\
+    /// If the text has an outline, the shadow includes it.
+    #[inline]
+    pub fn with_shadow(mut self, color: Color, offset: Vector2<i32>) -> Self {
+        self.shadow = Some((color, offset));
+        self
+    }
+
+    /// Add a per-character animation. Animations are applied in the order they were added.
+    #[inline]
+    pub fn with_animation(mut self, animation: TextAnimation) -> Self {
+        self.animations.push(animation);
+        self
+    }
+
+    /// Set the time in seconds since the animations started, e.g. since a dialogue box opened.
+    #[inline]
+    pub fn with_time(mut self, time: Duration) -> Self {
+        self.time = time.as_secs_f32();
+        self
+    }
+
+    /// Returns `true` if the animations show every character at the current time.\
+    /// Useful to wait for a typewriter animation to finish.
+    #[inline]
+    pub fn is_revealed(&self) -> bool {
+        self.layout
+            .glyphs()
+            .iter()
+            .enumerate()
+            .all(|(index, glyph)| self.state(index, glyph).visible)
+    }
+
+    /// Get the animated state of the `glyph` at `index`.
+    fn state(&self, index: usize, glyph: &GlyphPosition<Option<Color>>) -> GlyphState {
+        let mut state = GlyphState {
+            index,
+            character: glyph.parent,
+            offset: Vector2::from([0.0, 0.0]),
+            color: glyph.user_data.unwrap_or(self.color),
+            visible: true,
+        };
+
+        for animation in &self.animations {
+            animation.animate(&mut state, self.time);
+        }
+        state
+    }
+
+    /// Measure the area covered by the glyphs, after wrapping and alignment.\
+    /// Returns empty bounds at the position of the sketch, if there are no visible glyphs.
+    #[inline]
+    pub fn measure(&self) -> TextBounds {
+        let lines = self.layout.lines().map_or(0, |lines| lines.len());
+        let mut glyphs = self
+            .layout
+            .glyphs()
+            .iter()
+            .filter(|glyph| glyph.width > 0 && glyph.height > 0)
+            .peekable();
+
+        if glyphs.peek().is_none() {
+            return TextBounds {
+                x: self.pos.x as f32,
+                y: self.pos.y as f32,
+                lines,
+                ..Default::default()
+            };
+        }
+
+        let (mut left, mut top) = (f32::MAX, f32::MAX);
+        let (mut right, mut bottom) = (f32::MIN, f32::MIN);
+        for glyph in glyphs {
+            left = left.min(glyph.x);
+            top = top.min(glyph.y);
+            right = right.max(glyph.x + glyph.width as f32);
+            bottom = bottom.max(glyph.y + glyph.height as f32);
+        }
+
+        TextBounds {
+            x: self.pos.x as f32 + left,
+            y: self.pos.y as f32 + top,
+            width: right - left,
+            height: bottom - top,
+            lines,
+        }
+    }
+
+    /// Append `span` to the layout.
+    fn append(&mut self, span: &TextSpan) {
+        let font = if span.font < self.fonts.len() {
+            span.font
+        } else {
+            0
+        };
+
+        self.layout.append(
+            &self.fonts,
+            &TextStyle::with_user_data(&span.text, span.px, font, span.color),
+        );
+    }
+
+    /// Lay out all spans again after the settings changed.
+    fn relayout(&mut self) {
+        self.layout.reset(&self.settings);
+        for span in std::mem::take(&mut self.spans) {
+            self.append(&span);
+            self.spans.push(span);
+        }
+    }
+
+    /// Set whether to enable [Subpixel Anti-Aliasing](https://en.wikipedia.org/wiki/Subpixel_rendering).\
+    /// With it, every color channel gets blended with its own coverage, assuming horizontal RGB subpixels.
     #[inline]
     pub fn with_ssa(mut self, ssa: bool) -> Self {
         self.saa = ssa;
@@ -51,52 +526,387 @@ impl TextSketch {
 }
 
 impl Drawable for TextSketch {
+    /// Draw the shadows, then the outlines and finally the characters on top of `canvas`.
     #[inline]
     fn apply(&self, canvas: &mut Canvas) {
-        self.layout.glyphs().iter().for_each(|glyph| {
-            if self.saa {
-                let (metrics, buffer) = self.font.rasterize_config_subpixel(glyph.key);
-
-                let width = metrics.width as u32;
-                let height = metrics.height as u32;
-
-                let x0 = self.pos.x + glyph.x as u32;
-                let y0 = self.pos.y + glyph.y as u32;
-
-                for y in 0..height {
-                    for x in 0..width {
-                        let subpixel_alpha = buffer[(y * width + x) as usize * 3];
-                        if subpixel_alpha > 0 {
-                            let x_coord = x0 + x;
-                            let y_coord = y0 + y;
-                            let (r, g, b) = self.color.to_rgb();
-                            let subpixel_color = Color::from_rgba(r, g, b, subpixel_alpha);
-                            canvas.set_pixel(x_coord as usize, y_coord as usize, &subpixel_color);
-                        }
+        let mut raster = canvas.raster();
+        let glyphs: Vec<_> = self
+            .layout
+            .glyphs()
+            .iter()
+            .enumerate()
+            .map(|(index, glyph)| (glyph, self.state(index, glyph)))
+            .filter(|(glyph, state)| state.visible && glyph.width > 0 && glyph.height > 0)
+            .collect();
+
+        let origin = |glyph: &GlyphPosition<Option<Color>>, state: &GlyphState| {
+            (
+                self.pos.x as i32 + glyph.x as i32 + state.offset.x.round() as i32,
+                self.pos.y as i32 + glyph.y as i32 + state.offset.y.round() as i32,
+            )
+        };
+
+        // outlines and shadows use the grayscale coverage, grown by the outline width
+        let radius = self.outline.map_or(0, |(_, width)| width as usize);
+        let silhouettes: Vec<_> = if self.shadow.is_some() || radius > 0 {
+            glyphs
+                .iter()
+                .map(|(glyph, _)| {
+                    let font = &self.fonts[glyph.font_index];
+                    match radius {
+                        0 => font.glyph(glyph.key, false),
+                        _ => font.outline(glyph.key, radius),
                     }
-                }
-            } else {
-                let (metrics, buffer) = self.font.rasterize_config(glyph.key);
-
-                let width = metrics.width;
-                let height = metrics.height;
-
-                // Calculate the starting position to draw the glyph
-                let x0 = self.pos.x + glyph.x as u32;
-                let y0 = self.pos.y + glyph.y as u32;
-
-                // Draw the glyph to the canvas using the specified color
-                for y in 0..height {
-                    for x in 0..width {
-                        let alpha = buffer[y * width + x] as u8;
-                        if alpha > 0 {
-                            let x_coord = x0 + x as u32;
-                            let y_coord = y0 + y as u32;
-                            canvas.set_pixel(x_coord as usize, y_coord as usize, &self.color);
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let radius = radius as i32;
+
+        if let Some((color, offset)) = self.shadow {
+            for ((glyph, state), silhouette) in glyphs.iter().zip(&silhouettes) {
+                let (x, y) = origin(glyph, state);
+                let color = fade(color, state.color);
+                let (x, y) = (x - radius + offset.x, y - radius + offset.y);
+                let coverage =
+                    Coverage::gray(&silhouette.coverage, silhouette.width, silhouette.height);
+                self.gamma.blend(&mut raster, &coverage, x, y, color);
+            }
+        }
+
+        if let Some((color, _)) = self.outline.filter(|(_, width)| *width > 0) {
+            for ((glyph, state), silhouette) in glyphs.iter().zip(&silhouettes) {
+                let (x, y) = origin(glyph, state);
+                let color = fade(color, state.color);
+                let coverage =
+                    Coverage::gray(&silhouette.coverage, silhouette.width, silhouette.height);
+                self.gamma
+                    .blend(&mut raster, &coverage, x - radius, y - radius, color);
+            }
+        }
+
+        for (glyph, state) in &glyphs {
+            let (x, y) = origin(glyph, state);
+            let glyph = self.fonts[glyph.font_index].glyph(glyph.key, self.saa);
+            let coverage = Coverage {
+                data: &glyph.coverage,
+                width: glyph.width,
+                height: glyph.height,
+                subpixel: self.saa,
+            };
+            self.gamma.blend(&mut raster, &coverage, x, y, state.color);
+        }
+    }
+}
+
+/// Multiply the alpha of an effect `color` with the alpha of the character `color` it belongs to.
+#[inline]
+fn fade(color: Color, character: Color) -> Color {
+    color.with_alpha((color.alpha() as u32 * character.alpha() as u32 / 255) as u8)
+}
+
+/// Coverage of a rasterized glyph.
+struct Coverage<'a> {
+    data: &'a [u8],
+    width: usize,
+    height: usize,
+    /// Whether there are three values per pixel, one for each color channel.
+    subpixel: bool,
+}
+
+impl<'a> Coverage<'a> {
+    /// Grayscale coverage of `width` x `height` with one value per pixel.
+    #[inline]
+    fn gray(data: &'a [u8], width: usize, height: usize) -> Self {
+        Self {
+            data,
+            width,
+            height,
+            subpixel: false,
+        }
+    }
+
+    /// Coverage of the red, green and blue channel of the pixel at `x` and `y`.
+    #[inline]
+    fn get(&self, x: usize, y: usize) -> [u8; 3] {
+        let i = y * self.width + x;
+        if self.subpixel {
+            [self.data[i * 3], self.data[i * 3 + 1], self.data[i * 3 + 2]]
+        } else {
+            [self.data[i]; 3]
+        }
+    }
+}
+
+/// Lookup tables to blend glyph coverage in a color space with a gamma.
+struct Gamma {
+    /// Channel values decoded to linear space.
+    decode: [f32; 256],
+    /// Linear values with [Gamma::STEPS] steps encoded to channel values.
+    encode: Vec<u8>,
+}
+
+impl Gamma {
+    const STEPS: usize = 4096;
+    /// Maximum number of shared tables, so changing the gamma every frame doesn't grow the cache forever.
+    const MAX_SHARED: usize = 32;
+
+    /// Get the tables for `gamma` rounded to hundredths, which are built once and shared by all sketches.
+    fn shared(gamma: f32) -> Arc<Self> {
+        let key = if gamma > 0.0 {
+            ((gamma * 100.0).round() as u32).max(1)
+        } else {
+            100
+        };
+
+        let mut tables = Self::tables();
+        if let Some(table) = tables.get(&key) {
+            return table.clone();
+        }
+
+        if tables.len() >= Self::MAX_SHARED {
+            tables.clear();
+        }
+        let gamma = Arc::new(Self::new(key as f32 / 100.0));
+        tables.insert(key, gamma.clone());
+        gamma
+    }
+
+    /// Get the shared tables, keyed by the gamma in hundredths.
+    fn tables() -> MutexGuard<'static, HashMap<u32, Arc<Gamma>>> {
+        static TABLES: OnceLock<Mutex<HashMap<u32, Arc<Gamma>>>> = OnceLock::new();
+
+        TABLES
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn new(gamma: f32) -> Self {
+        let gamma = if gamma > 0.0 { gamma } else { 1.0 };
+        let mut decode = [0.0; 256];
+        for (i, value) in decode.iter_mut().enumerate() {
+            *value = (i as f32 / 255.0).powf(gamma);
+        }
+
+        let encode = (0..=Self::STEPS)
+            .map(|i| ((i as f32 / Self::STEPS as f32).powf(1.0 / gamma) * 255.0).round() as u8)
+            .collect();
+
+        Self { decode, encode }
+    }
+
+    /// Blend `color` with `coverage` at `x` and `y` on top of `raster`, one channel at a time.
+    fn blend(&self, raster: &mut Raster, coverage: &Coverage, x: i32, y: i32, color: Color) {
+        let src = [color.red(), color.green(), color.blue()].map(|c| self.decode[c as usize]);
+        let alpha = color.alpha() as f32 / 255.0;
+        let start_x = x.max(0);
+        let end_x = x + coverage.width as i32;
+
+        for dy in 0..coverage.height {
+            let canvas_y = y + dy as i32;
+            if canvas_y < 0 || end_x <= start_x {
+                continue;
+            }
+
+            raster.map_span(
+                canvas_y as usize,
+                start_x as usize,
+                end_x as usize,
+                |canvas_x, dst| {
+                    let values = coverage.get((canvas_x as i32 - x) as usize, dy);
+                    if values == [0; 3] {
+                        return dst;
+                    }
+
+                    let dst_alpha = dst.alpha() as f32 / 255.0;
+                    let dst_channels = [dst.red(), dst.green(), dst.blue()];
+                    let mut out = [0; 3];
+                    let mut out_alpha = 0.0;
+
+                    for c in 0..3 {
+                        // source over per channel, in the space of the gamma
+                        let a = values[c] as f32 / 255.0 * alpha;
+                        let a_out = a + dst_alpha * (1.0 - a);
+                        if a_out > 0.0 {
+                            let dst = self.decode[dst_channels[c] as usize];
+                            let value = (src[c] * a + dst * dst_alpha * (1.0 - a)) / a_out;
+                            out[c] = self.encode[(value * Self::STEPS as f32).round() as usize];
                         }
+                        out_alpha += a_out / 3.0;
                     }
-                }
+
+                    Color::from_rgba(out[0], out[1], out[2], (out_alpha * 255.0).round() as u8)
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::canvas::Canvas;
+    use crate::render::color::Color;
+    use crate::render::raster::Raster;
+    use crate::simplified::vec2;
+    use crate::text::effects::TextAnimation;
+    use crate::text::{
+        CacheStats, Coverage, Font, Gamma, HorizontalAlign, TextSketch, VerticalAlign,
+    };
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn font() -> Font {
+        Font::from_bytes(include_bytes!("../examples/assets/Roboto-Medium.ttf")).unwrap()
+    }
+
+    #[test]
+    fn caches_rasterized_glyphs() {
+        let font = font();
+        let mut canvas = Canvas::new(64, 32);
+        let sketch = TextSketch::with_font(vec2(0, 0), &font).with_text("abba", 16.0);
+
+        canvas.drawable(&sketch);
+        assert_eq!(
+            font.cache_stats(),
+            CacheStats {
+                glyphs: 2,
+                hits: 2,
+                misses: 2,
             }
-        });
+        );
+
+        canvas.drawable(&TextSketch::with_font(vec2(0, 0), &font.clone()).with_text("ab", 16.0));
+        assert_eq!(font.cache_stats().hits, 4);
+
+        font.clear_cache();
+        assert_eq!(font.cache_stats(), CacheStats::default());
+        assert!(Font::from_bytes(&[0, 1, 2]).is_none());
+    }
+
+    #[test]
+    fn caches_outlines_across_draws() {
+        let font = font();
+        let mut canvas = Canvas::new(64, 32);
+        let sketch = TextSketch::with_font(vec2(4, 4), &font)
+            .with_text("abba", 16.0)
+            .with_outline(Color::RED, 2)
+            .with_shadow(Color::BLACK, vec2(1, 1));
+
+        canvas.drawable(&sketch);
+        let first = font.cache_stats();
+        assert_eq!(font.cache().outlines.len(), 2);
+
+        canvas.drawable(&sketch);
+        let second = font.cache_stats();
+        assert_eq!(second.glyphs, first.glyphs);
+        assert_eq!(second.misses, first.misses);
+        assert!(second.hits > first.hits);
+    }
+
+    #[test]
+    fn wraps_words_at_max_width() {
+        let font = font();
+        let line = TextSketch::with_font(vec2(10, 5), &font).with_text("Hello World", 20.0);
+        let bounds = line.measure();
+        assert_eq!(bounds.lines, 1);
+        assert!(bounds.x >= 10.0 && bounds.y >= 5.0);
+
+        let wrapped = line
+            .with_max_width(bounds.width * 0.75)
+            .with_line_spacing(2.0);
+        let wrapped_bounds = wrapped.measure();
+        assert_eq!(wrapped_bounds.lines, 2);
+        assert!(wrapped_bounds.width < bounds.width);
+        assert!(wrapped_bounds.height > bounds.height * 2.0);
+    }
+
+    #[test]
+    fn aligns_within_bounds() {
+        let font = font();
+        let sketch = |horizontal, vertical| {
+            TextSketch::with_font(vec2(0, 0), &font)
+                .with_text("Qilin", 20.0)
+                .with_bounds(200.0, 100.0)
+                .with_align(horizontal, vertical)
+                .measure()
+        };
+
+        let start = sketch(HorizontalAlign::Left, VerticalAlign::Top);
+        let center = sketch(HorizontalAlign::Center, VerticalAlign::Middle);
+        let end = sketch(HorizontalAlign::Right, VerticalAlign::Bottom);
+
+        assert!((center.x + center.width / 2.0 - 100.0).abs() < 2.0);
+        assert!(end.x + end.width <= 200.0 && end.x + end.width > 195.0);
+        assert!(start.y < center.y && center.y < end.y);
+        assert_eq!(start.width, end.width);
+    }
+
+    #[test]
+    fn reveals_with_typewriter() {
+        let font = font();
+        let sketch = |millis| {
+            TextSketch::with_font(vec2(0, 0), &font)
+                .with_text("Hi!", 16.0)
+                .with_animation(TextAnimation::Typewriter { speed: 10.0 })
+                .with_time(Duration::from_millis(millis))
+        };
+
+        let mut canvas = Canvas::new(32, 24);
+        canvas.drawable(&sketch(0));
+        assert!(canvas
+            .get_buffer()
+            .as_slice()
+            .iter()
+            .all(|px| *px == Color::BLACK.0));
+
+        assert!(!sketch(250).is_revealed());
+        assert!(sketch(300).is_revealed());
+    }
+
+    #[test]
+    fn blends_each_channel_with_its_coverage() {
+        let mut pixels = vec![Color::BLUE.0, Color::TRANSPARENT.0];
+        let coverage = Coverage {
+            data: &[255, 0, 0, 128, 128, 128],
+            width: 2,
+            height: 1,
+            subpixel: true,
+        };
+        Gamma::shared(1.0).blend(
+            &mut Raster::new(&mut pixels, 2, 0),
+            &coverage,
+            0,
+            0,
+            Color::WHITE,
+        );
+
+        assert_eq!(Color(pixels[0]), Color::MAGENTA);
+        assert_eq!(Color(pixels[1]), Color::WHITE.with_alpha(128));
+
+        let mut pixels = vec![Color::BLACK.0];
+        let gray = Coverage::gray(&[128], 1, 1);
+        Gamma::shared(2.2).blend(
+            &mut Raster::new(&mut pixels, 1, 0),
+            &gray,
+            0,
+            0,
+            Color::WHITE,
+        );
+        assert!(Color(pixels[0]).red() > 180);
+
+        assert!(Arc::ptr_eq(&Gamma::shared(2.2), &Gamma::shared(2.2)));
+        assert!(Arc::ptr_eq(&Gamma::shared(2.2), &Gamma::shared(2.201)));
+        assert!(Arc::ptr_eq(&Gamma::shared(-1.0), &Gamma::shared(1.0)));
+        assert!(Arc::ptr_eq(&Gamma::shared(f32::NAN), &Gamma::shared(1.0)));
+    }
+
+    #[test]
+    fn limits_shared_gamma_tables() {
+        for i in 1..=Gamma::MAX_SHARED * 3 {
+            Gamma::shared(i as f32 / 7.0);
+        }
+        assert!(Gamma::tables().len() <= Gamma::MAX_SHARED);
     }
 }
@@ -0,0 +1,170 @@
+use crate::render::color::Color;
+use mint::Vector2;
+use std::f32::consts::TAU;
+
+/// State of one character, which [TextAnimation]s change before it gets drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphState {
+    /// Index of the character in the text, counting whitespace and line breaks.
+    pub index: usize,
+    /// The character.
+    pub character: char,
+    /// Offset from the position in the layout in pixels.
+    pub offset: Vector2<f32>,
+    /// Color of the character. Its alpha also fades the outline and shadow.
+    pub color: Color,
+    /// Whether the character gets drawn.
+    pub visible: bool,
+}
+
+/// Per-character animation of a [crate::text::TextSketch], driven by [crate::text::TextSketch::with_time].
+#[derive(Clone, Copy, Debug)]
+pub enum TextAnimation {
+    /// Reveal `speed` characters per second, like a typewriter.
+    Typewriter { speed: f32 },
+    /// Move characters on a sine wave with `amplitude` in pixels, `speed` waves per second
+    /// and `length` characters per wave.
+    Wave {
+        amplitude: f32,
+        speed: f32,
+        length: f32,
+    },
+    /// Jitter characters by up to `amplitude` pixels, changing direction `speed` times per second.
+    Shake { amplitude: f32, speed: f32 },
+    /// Change the state of every character, given the time in seconds.
+    Custom(fn(&mut GlyphState, f32)),
+}
+
+impl TextAnimation {
+    /// Change `state` at `time` in seconds.
+    pub(crate) fn animate(&self, state: &mut GlyphState, time: f32) {
+        match self {
+            Self::Typewriter { speed } => {
+                if state.index as f32 >= (time * speed).floor() {
+                    state.visible = false;
+                }
+            }
+            Self::Wave {
+                amplitude,
+                speed,
+                length,
+            } => {
+                let phase = time * speed - state.index as f32 / length.max(f32::EPSILON);
+                state.offset.y += amplitude * (phase * TAU).sin();
+            }
+            Self::Shake { amplitude, speed } => {
+                let step = (time * speed).floor() as u32;
+                let mut hash = (state.index as u32).wrapping_mul(0x9E37_79B9)
+                    ^ step.wrapping_mul(0x85EB_CA6B)
+                    ^ 0x2545_F491;
+                let mut random = || {
+                    // xorshift, mapped to -1.0..1.0
+                    hash ^= hash << 13;
+                    hash ^= hash >> 17;
+                    hash ^= hash << 5;
+                    (hash % 2001) as f32 / 1000.0 - 1.0
+                };
+
+                state.offset.x += amplitude * random();
+                state.offset.y += amplitude * random();
+            }
+            Self::Custom(f) => f(state, time),
+        }
+    }
+}
+
+/// Grow the `coverage` of `width` x `height` by `radius` pixels in every direction.\
+/// The result is `2 * radius` pixels wider and higher.
+pub(crate) fn dilate(coverage: &[u8], width: usize, height: usize, radius: usize) -> Vec<u8> {
+    let (out_width, out_height) = (width + 2 * radius, height + 2 * radius);
+    let mut out = vec![0u8; out_width * out_height];
+    let r = radius as isize;
+
+    let offsets: Vec<(isize, isize)> = (-r..=r)
+        .flat_map(|dy| (-r..=r).map(move |dx| (dx, dy)))
+        .filter(|(dx, dy)| dx * dx + dy * dy <= r * r)
+        .collect();
+
+    for y in 0..height {
+        for x in 0..width {
+            let value = coverage[y * width + x];
+            if value == 0 {
+                continue;
+            }
+
+            for (dx, dy) in &offsets {
+                let ox = (x as isize + r + dx) as usize;
+                let oy = (y as isize + r + dy) as usize;
+                let px = &mut out[oy * out_width + ox];
+                *px = (*px).max(value);
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::color::Color;
+    use crate::simplified::vec2;
+    use crate::text::effects::{dilate, GlyphState, TextAnimation};
+
+    fn state(index: usize) -> GlyphState {
+        GlyphState {
+            index,
+            character: 'a',
+            offset: vec2(0.0, 0.0),
+            color: Color::WHITE,
+            visible: true,
+        }
+    }
+
+    #[test]
+    fn typewriter_reveals_over_time() {
+        let typewriter = TextAnimation::Typewriter { speed: 10.0 };
+        let (mut first, mut third) = (state(0), state(2));
+
+        typewriter.animate(&mut first, 0.25);
+        typewriter.animate(&mut third, 0.25);
+        assert!(first.visible);
+        assert!(!third.visible);
+    }
+
+    #[test]
+    fn animations_offset_characters() {
+        let mut glyph = state(1);
+        TextAnimation::Wave {
+            amplitude: 2.0,
+            speed: 1.0,
+            length: 4.0,
+        }
+        .animate(&mut glyph, 0.0);
+        assert!((glyph.offset.y + 2.0).abs() < 1e-4);
+
+        let shake = TextAnimation::Shake {
+            amplitude: 3.0,
+            speed: 10.0,
+        };
+        let (mut a, mut b) = (state(4), state(4));
+        shake.animate(&mut a, 0.51);
+        shake.animate(&mut b, 0.55);
+        assert_eq!(a.offset, b.offset);
+        assert!(a.offset.x.abs() <= 3.0 && a.offset.y.abs() <= 3.0);
+
+        TextAnimation::Custom(|state, time| state.offset.x = time).animate(&mut glyph, 0.5);
+        assert_eq!(glyph.offset.x, 0.5);
+    }
+
+    #[test]
+    fn dilates_coverage() {
+        let out = dilate(&[255], 1, 1, 1);
+
+        #[rustfmt::skip]
+        assert_eq!(out, vec![
+            0, 255, 0,
+            255, 255, 255,
+            0, 255, 0,
+        ]);
+    }
+}
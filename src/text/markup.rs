@@ -0,0 +1,157 @@
+use crate::render::color::Color;
+use crate::text::TextSpan;
+
+/// Fonts selected by the `[b]` and `[i]` tags.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct MarkupFonts {
+    pub bold: usize,
+    pub italic: usize,
+}
+
+/// Split `markup` into spans, which start with the style of `base`.\
+/// Supports `[color=red]`, `[color=#FF0000]`, `[size=24]`, `[font=1]`, `[b]` and `[i]`,
+/// each closed by the matching `[/tag]`. `[[` is a literal `[`.
+/// Unknown, invalid or unmatched closing tags are kept as text, unclosed tags apply to the rest of `markup`.
+pub(crate) fn parse(markup: &str, base: &TextSpan, fonts: MarkupFonts) -> Vec<TextSpan> {
+    let mut spans = Vec::new();
+    let mut stack: Vec<(&str, TextSpan)> = Vec::new();
+    let mut style = base.clone();
+    let mut rest = markup;
+
+    while let Some(start) = rest.find('[') {
+        style.text.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(escaped) = rest.strip_prefix("[[") {
+            style.text.push('[');
+            rest = escaped;
+            continue;
+        }
+
+        let Some(end) = rest.find(']') else {
+            break;
+        };
+        let tag = &rest[1..end];
+
+        if let Some(name) = tag.strip_prefix('/') {
+            if stack.last().is_some_and(|(open, _)| *open == name) {
+                if let Some((_, outer)) = stack.pop() {
+                    flush(&mut spans, &mut style, outer);
+                }
+                rest = &rest[end + 1..];
+                continue;
+            }
+        } else if let Some((name, next)) = open(tag, &style, fonts) {
+            let outer = TextSpan {
+                text: String::new(),
+                ..style.clone()
+            };
+            stack.push((name, outer));
+            flush(&mut spans, &mut style, next);
+            rest = &rest[end + 1..];
+            continue;
+        }
+
+        // keep invalid tags as text
+        style.text.push('[');
+        rest = &rest[1..];
+    }
+
+    style.text.push_str(rest);
+    if !style.text.is_empty() {
+        spans.push(style);
+    }
+    spans
+}
+
+/// Returns the name of the opening `tag` and the style of the text inside of it.
+fn open<'a>(tag: &'a str, style: &TextSpan, fonts: MarkupFonts) -> Option<(&'a str, TextSpan)> {
+    let (name, value) = match tag.split_once('=') {
+        Some((name, value)) => (name.trim(), Some(value.trim())),
+        None => (tag.trim(), None),
+    };
+
+    let next = TextSpan {
+        text: String::new(),
+        ..style.clone()
+    };
+    let next = match (name, value) {
+        ("color", Some(value)) => {
+            next.with_color(Color::from_name(value).or_else(|| Color::from_hex(value))?)
+        }
+        ("size", Some(value)) => {
+            next.with_size(
+                value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|px| px.is_finite() && *px > 0.0)?,
+            )
+        }
+        ("font", Some(value)) => next.with_font(value.parse().ok()?),
+        ("b", None) => next.with_font(fonts.bold),
+        ("i", None) => next.with_font(fonts.italic),
+        _ => return None,
+    };
+
+    Some((name, next))
+}
+
+/// Finish the current span, if it has text, and continue with the style of `next`.
+fn flush(spans: &mut Vec<TextSpan>, style: &mut TextSpan, next: TextSpan) {
+    let done = std::mem::replace(style, next);
+    if !done.text.is_empty() {
+        spans.push(done);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::color::Color;
+    use crate::text::markup::{parse, MarkupFonts};
+    use crate::text::TextSpan;
+
+    const FONTS: MarkupFonts = MarkupFonts { bold: 1, italic: 2 };
+
+    #[test]
+    fn parses_nested_tags() {
+        let spans = parse(
+            "Run, [color=red]it's [b]DANGER[/b][/color] [size=8]ahead[/size]!",
+            &TextSpan::new("", 16.0),
+            FONTS,
+        );
+
+        assert_eq!(
+            spans,
+            vec![
+                TextSpan::new("Run, ", 16.0),
+                TextSpan::new("it's ", 16.0).with_color(Color::RED),
+                TextSpan::new("DANGER", 16.0)
+                    .with_color(Color::RED)
+                    .with_font(1),
+                TextSpan::new(" ", 16.0),
+                TextSpan::new("ahead", 8.0),
+                TextSpan::new("!", 16.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_invalid_tags_as_text() {
+        let spans = parse(
+            "[[b] [color=nope]x[/color] [size=NaN][size=inf][size=0][size=-2]z [i]y[/b] [size=1",
+            &TextSpan::new("", 10.0),
+            FONTS,
+        );
+
+        assert_eq!(
+            spans,
+            vec![
+                TextSpan::new(
+                    "[b] [color=nope]x[/color] [size=NaN][size=inf][size=0][size=-2]z ",
+                    10.0,
+                ),
+                TextSpan::new("y[/b] [size=1", 10.0).with_font(2),
+            ]
+        );
+    }
+}
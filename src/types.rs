@@ -1,5 +1,8 @@
+use crate::game::input::InputMap;
 use crate::render::color::Color;
-use minifb::{Scale, ScaleMode, WindowOptions};
+use crate::render::post::PostProcess;
+use crate::render::viewport::LogicalResolution;
+use minifb::{Key, MouseButton, Scale, ScaleMode, WindowOptions};
 use std::time::Duration;
 
 /// Game Configuration containing title, max fps, width, height and window options.
@@ -17,6 +20,22 @@ pub struct GameConfig {
     pub fixed_time_step: TimeStamp,
     /// Window options.
     pub window: WindowOptions,
+    /// Only redraw what changed. Default is `false`.\
+    /// If enabled, the canvas doesn't get cleared every frame and only gets presented
+    /// when something got drawn. See [crate::render::canvas::Canvas::set_dirty_tracking].\
+    /// With a [GameConfig::logical_resolution], only the dirty regions get scaled to the window.
+    /// Active post-processing always processes and presents the whole frame.
+    pub dirty_tracking: bool,
+    /// Screen effects applied to the canvas before presenting it. Default is an empty chain.\
+    /// Can be changed at runtime using [crate::game::context::GameContext::post_process].
+    pub post_process: PostProcess,
+    /// Fixed size of the canvas, which gets scaled to the window with bars around it. Default is [None].\
+    /// If [None], the canvas has the size of [GameConfig::width] and [GameConfig::height]
+    /// and gets scaled by the [GameConfig::window] options.
+    pub logical_resolution: Option<LogicalResolution>,
+    /// Named actions and axes. Default is an empty map.\
+    /// Can be queried and rebound at runtime using [crate::game::context::GameContext::input_map].
+    pub input_map: InputMap,
 }
 
 impl Default for GameConfig {
@@ -38,6 +57,10 @@ impl Default for GameConfig {
                 transparency: false,
                 none: false,
             },
+            dirty_tracking: false,
+            post_process: PostProcess::new(),
+            logical_resolution: None,
+            input_map: InputMap::new(),
         }
     }
 }
@@ -60,8 +83,155 @@ pub const FPS120: Duration = Duration::from_nanos(8333333);
 /// Vector of colors to represent an image.
 pub type Image = Vec<Color>;
 
-#[derive(Clone, Copy, Debug)]
+/// A key or mouse button.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Button {
     Keyboard(minifb::Key),
     Mouse(minifb::MouseButton),
 }
+
+/// Every [Key], except [Key::Unknown] and [Key::Count], with the name stored in saved bindings.\
+/// The names must never change, otherwise saved bindings get lost.
+const KEYS: [(Key, &str); 106] = [
+    (Key::Key0, "Key0"),
+    (Key::Key1, "Key1"),
+    (Key::Key2, "Key2"),
+    (Key::Key3, "Key3"),
+    (Key::Key4, "Key4"),
+    (Key::Key5, "Key5"),
+    (Key::Key6, "Key6"),
+    (Key::Key7, "Key7"),
+    (Key::Key8, "Key8"),
+    (Key::Key9, "Key9"),
+    (Key::A, "A"),
+    (Key::B, "B"),
+    (Key::C, "C"),
+    (Key::D, "D"),
+    (Key::E, "E"),
+    (Key::F, "F"),
+    (Key::G, "G"),
+    (Key::H, "H"),
+    (Key::I, "I"),
+    (Key::J, "J"),
+    (Key::K, "K"),
+    (Key::L, "L"),
+    (Key::M, "M"),
+    (Key::N, "N"),
+    (Key::O, "O"),
+    (Key::P, "P"),
+    (Key::Q, "Q"),
+    (Key::R, "R"),
+    (Key::S, "S"),
+    (Key::T, "T"),
+    (Key::U, "U"),
+    (Key::V, "V"),
+    (Key::W, "W"),
+    (Key::X, "X"),
+    (Key::Y, "Y"),
+    (Key::Z, "Z"),
+    (Key::F1, "F1"),
+    (Key::F2, "F2"),
+    (Key::F3, "F3"),
+    (Key::F4, "F4"),
+    (Key::F5, "F5"),
+    (Key::F6, "F6"),
+    (Key::F7, "F7"),
+    (Key::F8, "F8"),
+    (Key::F9, "F9"),
+    (Key::F10, "F10"),
+    (Key::F11, "F11"),
+    (Key::F12, "F12"),
+    (Key::F13, "F13"),
+    (Key::F14, "F14"),
+    (Key::F15, "F15"),
+    (Key::Down, "Down"),
+    (Key::Left, "Left"),
+    (Key::Right, "Right"),
+    (Key::Up, "Up"),
+    (Key::Apostrophe, "Apostrophe"),
+    (Key::Backquote, "Backquote"),
+    (Key::Backslash, "Backslash"),
+    (Key::Comma, "Comma"),
+    (Key::Equal, "Equal"),
+    (Key::LeftBracket, "LeftBracket"),
+    (Key::Minus, "Minus"),
+    (Key::Period, "Period"),
+    (Key::RightBracket, "RightBracket"),
+    (Key::Semicolon, "Semicolon"),
+    (Key::Slash, "Slash"),
+    (Key::Backspace, "Backspace"),
+    (Key::Delete, "Delete"),
+    (Key::End, "End"),
+    (Key::Enter, "Enter"),
+    (Key::Escape, "Escape"),
+    (Key::Home, "Home"),
+    (Key::Insert, "Insert"),
+    (Key::Menu, "Menu"),
+    (Key::PageDown, "PageDown"),
+    (Key::PageUp, "PageUp"),
+    (Key::Pause, "Pause"),
+    (Key::Space, "Space"),
+    (Key::Tab, "Tab"),
+    (Key::NumLock, "NumLock"),
+    (Key::CapsLock, "CapsLock"),
+    (Key::ScrollLock, "ScrollLock"),
+    (Key::LeftShift, "LeftShift"),
+    (Key::RightShift, "RightShift"),
+    (Key::LeftCtrl, "LeftCtrl"),
+    (Key::RightCtrl, "RightCtrl"),
+    (Key::NumPad0, "NumPad0"),
+    (Key::NumPad1, "NumPad1"),
+    (Key::NumPad2, "NumPad2"),
+    (Key::NumPad3, "NumPad3"),
+    (Key::NumPad4, "NumPad4"),
+    (Key::NumPad5, "NumPad5"),
+    (Key::NumPad6, "NumPad6"),
+    (Key::NumPad7, "NumPad7"),
+    (Key::NumPad8, "NumPad8"),
+    (Key::NumPad9, "NumPad9"),
+    (Key::NumPadDot, "NumPadDot"),
+    (Key::NumPadSlash, "NumPadSlash"),
+    (Key::NumPadAsterisk, "NumPadAsterisk"),
+    (Key::NumPadMinus, "NumPadMinus"),
+    (Key::NumPadPlus, "NumPadPlus"),
+    (Key::NumPadEnter, "NumPadEnter"),
+    (Key::LeftAlt, "LeftAlt"),
+    (Key::RightAlt, "RightAlt"),
+    (Key::LeftSuper, "LeftSuper"),
+    (Key::RightSuper, "RightSuper"),
+];
+
+impl Button {
+    /// Get a stable name of the button, e.g. `"W"`, `"Space"` or `"MouseLeft"`.\
+    /// Returns `"Unknown"` for [Key::Unknown] and [Key::Count].
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Button::Keyboard(key) => {
+                KEYS.iter()
+                    .find(|(other, _)| other == key)
+                    .map_or("Unknown", |(_, name)| name)
+            }
+            Button::Mouse(MouseButton::Left) => "MouseLeft",
+            Button::Mouse(MouseButton::Middle) => "MouseMiddle",
+            Button::Mouse(MouseButton::Right) => "MouseRight",
+        }
+    }
+
+    /// Get the button with a `name` from [Button::name].
+    ///
+    /// Returns [None] if there is no button with that name.
+    #[inline]
+    pub fn from_name(name: &str) -> Option<Button> {
+        match name {
+            "MouseLeft" => Some(Button::Mouse(MouseButton::Left)),
+            "MouseMiddle" => Some(Button::Mouse(MouseButton::Middle)),
+            "MouseRight" => Some(Button::Mouse(MouseButton::Right)),
+            _ => {
+                KEYS.iter()
+                    .find(|(_, other)| *other == name)
+                    .map(|(key, _)| Button::Keyboard(*key))
+            }
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Golden-image snapshot testing.
+//!
+//! Renders into an off-screen [Canvas] and compares the result with a reference image in
+//! `tests/golden`. References are binary PPM files, so no optional features are required.\
+//! Run the tests with `QILIN_BLESS=1` to create or update the references after intended changes.
+//! On failure, the actual image and a diff image are written to the cargo target tmp directory.
+
+use qilin::render::canvas::Canvas;
+use qilin::render::color::Color;
+use qilin::render::sketch::Drawable;
+use std::path::{Path, PathBuf};
+
+/// Width of canvases created by [render].
+pub const WIDTH: usize = 64;
+/// Height of canvases created by [render].
+pub const HEIGHT: usize = 48;
+
+/// How much a rendered image may differ from its reference.
+#[derive(Clone, Copy, Debug)]
+pub struct Tolerance {
+    /// Maximum difference of a single color channel, before a pixel counts as different.
+    pub channel: u8,
+    /// Maximum number of different pixels.
+    pub pixels: usize,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            channel: 2,
+            pixels: 0,
+        }
+    }
+}
+
+/// Render `drawable` into a new black canvas of [WIDTH] x [HEIGHT].
+pub fn render<T: Drawable>(drawable: &T) -> Canvas {
+    let mut canvas = Canvas::new(WIDTH, HEIGHT);
+    canvas.clear(&Color::BLACK);
+    canvas.drawable(drawable);
+    canvas
+}
+
+/// Compare `canvas` with the reference `name` using the default [Tolerance].
+pub fn assert_golden(name: &str, canvas: &Canvas) {
+    assert_golden_with(name, canvas, Tolerance::default());
+}
+
+/// Compare `canvas` with the reference `name` using `tolerance`.
+pub fn assert_golden_with(name: &str, canvas: &Canvas, tolerance: Tolerance) {
+    let reference = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.ppm"));
+
+    if std::env::var_os("QILIN_BLESS").is_some() {
+        canvas
+            .save_ppm(&reference)
+            .expect("failed to write reference");
+        return;
+    }
+
+    let expected = match std::fs::read(&reference) {
+        Ok(bytes) => read_ppm(&bytes).expect("invalid reference image"),
+        Err(err) => {
+            panic!(
+                "missing reference {}: {err}\nrun with QILIN_BLESS=1 to create it",
+                reference.display()
+            )
+        }
+    };
+
+    let actual = read_ppm(&to_ppm(canvas)).expect("invalid canvas");
+    assert_eq!(
+        (actual.width, actual.height),
+        (expected.width, expected.height),
+        "size of {name} differs from its reference"
+    );
+
+    let mut diff = Canvas::new(actual.width, actual.height);
+    let mut different = 0;
+    let mut max_channel = 0;
+
+    for (i, (a, e)) in actual.pixels.iter().zip(&expected.pixels).enumerate() {
+        let channel = a
+            .iter()
+            .zip(e)
+            .map(|(a, e)| a.abs_diff(*e))
+            .max()
+            .unwrap_or(0);
+        max_channel = max_channel.max(channel);
+
+        // highlight different pixels in red over the darkened reference
+        let color = if channel > tolerance.channel {
+            different += 1;
+            Color::RED
+        } else {
+            Color::from_rgb(e[0] / 4, e[1] / 4, e[2] / 4)
+        };
+        diff.set_pixel(i % actual.width, i / actual.width, &color);
+    }
+
+    if different > tolerance.pixels {
+        let dir = output_dir();
+        let actual_path = dir.join(format!("{name}.actual.ppm"));
+        let diff_path = dir.join(format!("{name}.diff.ppm"));
+        canvas
+            .save_ppm(&actual_path)
+            .expect("failed to write actual image");
+        diff.save_ppm(&diff_path)
+            .expect("failed to write diff image");
+
+        panic!(
+            "{name} differs from its reference in {different} pixels (max channel difference \
+             {max_channel}, tolerance {tolerance:?})\nactual: {}\ndiff: {}",
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+/// Decoded PPM image with RGB pixels.
+struct Ppm {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+/// Encode `canvas` as PPM.
+fn to_ppm(canvas: &Canvas) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    canvas
+        .write_ppm(&mut bytes)
+        .expect("failed to encode canvas");
+    bytes
+}
+
+/// Decode a binary PPM with a maximum value of 255, as written by [Canvas::write_ppm].
+fn read_ppm(bytes: &[u8]) -> Option<Ppm> {
+    // the header consists of 4 whitespace separated tokens followed by a single whitespace
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while tokens.len() < 4 {
+        while bytes.get(pos)?.is_ascii_whitespace() {
+            pos += 1;
+        }
+        let start = pos;
+        while !bytes.get(pos)?.is_ascii_whitespace() {
+            pos += 1;
+        }
+        tokens.push(std::str::from_utf8(&bytes[start..pos]).ok()?);
+    }
+
+    if tokens[0] != "P6" || tokens[3] != "255" {
+        return None;
+    }
+
+    let width = tokens[1].parse().ok()?;
+    let height = tokens[2].parse().ok()?;
+    let data = bytes.get(pos + 1..)?;
+    if data.len() != width * height * 3 {
+        return None;
+    }
+
+    Some(Ppm {
+        width,
+        height,
+        pixels: data
+            .chunks_exact(3)
+            .map(|px| [px[0], px[1], px[2]])
+            .collect(),
+    })
+}
+
+/// Directory for actual and diff images of failed comparisons.
+fn output_dir() -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    std::fs::create_dir_all(&dir).expect("failed to create output directory");
+    dir
+}
@@ -0,0 +1,457 @@
+//! Golden-image tests covering every [Operation] and [qilin::text::TextSketch].\
+//! Run with `QILIN_BLESS=1` to update the references in `tests/golden` after intended changes.
+
+mod common;
+
+use common::{assert_golden, render};
+use qilin::render::bitmap_font::{BitmapFont, BitmapText};
+use qilin::render::canvas::Canvas;
+use qilin::render::color::Color;
+use qilin::render::fill::{LinearGradient, RadialGradient};
+use qilin::render::indexed::IndexedImage;
+use qilin::render::lighting::{Light, Lighting, Occluder};
+use qilin::render::nine_slice::{Insets, NineSlice, SliceMode};
+use qilin::render::sketch::{Operation, Sketch};
+use qilin::simplified::vec2;
+use std::sync::Arc;
+
+#[test]
+fn line() {
+    let mut sketch = Sketch::new();
+    sketch
+        .line(vec2(2, 2), vec2(61, 45), Color::WHITE)
+        .line(vec2(61, 2), vec2(2, 45), Color::RED)
+        .line(vec2(32, 0), vec2(36, 47), Color::GREEN)
+        .line(vec2(0, 24), vec2(63, 20), Color::BLUE.with_alpha(128));
+
+    assert_golden("line", &render(&sketch));
+}
+
+#[test]
+fn circle() {
+    let mut sketch = Sketch::new();
+    sketch
+        .circle(vec2(20, 24), 16, Color::GREEN)
+        .circle(vec2(44, 24), 16, Color::RED.with_alpha(128))
+        .circle(vec2(60, 4), 10, Color::BLUE);
+
+    assert_golden("circle", &render(&sketch));
+}
+
+#[test]
+fn circle_radial_gradient() {
+    let gradient = RadialGradient::new(vec2(32, 24), 20)
+        .with_stop(0.0, Color::WHITE)
+        .with_stop(0.5, Color::YELLOW)
+        .with_stop(1.0, Color::RED);
+
+    assert_golden(
+        "circle_radial_gradient",
+        &render(Sketch::new().circle(vec2(32, 24), 20, gradient)),
+    );
+}
+
+#[test]
+fn rect() {
+    let mut sketch = Sketch::new();
+    sketch
+        .rect(vec2(4, 4), 30, 20, Color::BLUE)
+        .rect(vec2(20, 14), 30, 20, Color::YELLOW.with_alpha(100))
+        .rect(vec2(50, 36), 30, 30, Color::GREEN);
+
+    assert_golden("rect", &render(&sketch));
+}
+
+#[test]
+fn rect_linear_gradient() {
+    let gradient = LinearGradient::new(vec2(8, 8), vec2(56, 40))
+        .with_stop(0.0, Color::CYAN)
+        .with_stop(1.0, Color::MAGENTA.with_alpha(64));
+
+    assert_golden(
+        "rect_linear_gradient",
+        &render(Sketch::new().rect(vec2(8, 8), 48, 32, gradient)),
+    );
+}
+
+#[test]
+fn image() {
+    let (width, height) = (24, 16);
+    let data: Vec<Color> = (0..width * height)
+        .map(|i| {
+            let (x, y) = (i % width, i / width);
+            Color::from_rgba(
+                (x * 10) as u8,
+                (y * 15) as u8,
+                128,
+                if x < 12 { 255 } else { 128 },
+            )
+        })
+        .collect();
+
+    let mut sketch = Sketch::new();
+    sketch
+        .rect(vec2(0, 20), 64, 8, Color::WHITE)
+        .image(vec2(8, 12), width as u32, height as u32, data.clone())
+        .image(vec2(50, 40), width as u32, height as u32, data);
+
+    assert_golden("image", &render(&sketch));
+}
+
+#[test]
+fn oval() {
+    let mut sketch = Sketch::new();
+    sketch.oval(vec2(4, 8), 40, 24, Color::ORANGE).oval(
+        vec2(30, 4),
+        20,
+        40,
+        Color::CYAN.with_alpha(160),
+    );
+
+    assert_golden("oval", &render(&sketch));
+}
+
+#[test]
+fn polygon() {
+    let mut sketch = Sketch::new();
+    sketch
+        .polygon(vec![vec2(4, 4), vec2(30, 10), vec2(16, 40)], Color::MAGENTA)
+        // concave star, filled with the even-odd rule
+        .polygon(
+            vec![
+                vec2(46, 2),
+                vec2(52, 20),
+                vec2(62, 20),
+                vec2(54, 30),
+                vec2(58, 46),
+                vec2(46, 36),
+                vec2(34, 46),
+                vec2(38, 30),
+                vec2(30, 20),
+                vec2(40, 20),
+            ],
+            Color::YELLOW,
+        );
+
+    assert_golden("polygon", &render(&sketch));
+}
+
+#[test]
+fn hidden_operations() {
+    let mut sketch = Sketch::new();
+    sketch.rect(vec2(4, 4), 20, 20, Color::RED);
+    let hidden = sketch.last_handle().unwrap();
+    sketch.circle(vec2(40, 24), 12, Color::GREEN);
+    sketch.set_visible(hidden, false);
+
+    assert_golden("hidden_operations", &render(&sketch));
+}
+
+#[test]
+fn single_operation() {
+    let op = Operation::Rect {
+        pos: vec2(16, 12),
+        width: 32,
+        height: 24,
+        fill: Color::GRAY.into(),
+    };
+
+    assert_golden("single_operation", &render(&op));
+}
+
+#[test]
+fn shared_image() {
+    let data = Arc::new(vec![Color::GREEN; 16 * 16]);
+
+    let mut sketch = Sketch::new();
+    sketch
+        .image(vec2(4, 4), 16, 16, data.clone())
+        .image(vec2(24, 24), 16, 16, data);
+
+    assert_golden("shared_image", &render(&sketch));
+}
+
+#[test]
+fn canvas() {
+    let mut target = Canvas::new(16, 12);
+    target.clear(&Color::TRANSPARENT);
+    target.draw(
+        Sketch::new()
+            .rect(vec2(0, 0), 16, 12, Color::BLUE.with_alpha(200))
+            .circle(vec2(8, 6), 4, Color::YELLOW),
+    );
+
+    let mut canvas = render(Sketch::new().rect(vec2(0, 20), 64, 8, Color::WHITE));
+    canvas.drawable(&target.as_image(vec2(2, 2)));
+    canvas.drawable(
+        &target
+            .as_image(vec2(22, 10))
+            .with_size(40, 30)
+            .with_opacity(128),
+    );
+    canvas.draw(Sketch::new().canvas(vec2(2, 30), 8, 6, target, 255));
+
+    assert_golden("canvas", &canvas);
+}
+
+#[test]
+fn indexed_image() {
+    let indices: Vec<u8> = (0..16 * 16)
+        .map(|i| ((i % 16) / 4 + (i / 64)) as u8 % 4)
+        .collect();
+    let palette = vec![Color::TRANSPARENT, Color::RED, Color::ORANGE, Color::YELLOW];
+    let swapped = vec![Color::TRANSPARENT, Color::BLUE, Color::CYAN, Color::WHITE];
+
+    let mut image = IndexedImage::new(vec2(4, 4), 16, 16, indices, palette)
+        .unwrap()
+        .with_palette(swapped);
+    let mut canvas = render(&image);
+
+    image.set_palette(1).unwrap();
+    image.set_pos(vec2(28, 20));
+    canvas.drawable(&image);
+
+    assert_golden("indexed_image", &canvas);
+}
+
+#[test]
+fn nine_slice() {
+    // 8x8 frame with a 2 pixel white border, a red outline and a checkered center
+    let data: Vec<Color> = (0..64)
+        .map(|i| {
+            let (x, y) = (i % 8, i / 8);
+            if x == 0 || y == 0 || x == 7 || y == 7 {
+                Color::RED
+            } else if x == 1 || y == 1 || x == 6 || y == 6 {
+                Color::WHITE
+            } else if (x + y) % 2 == 0 {
+                Color::BLUE
+            } else {
+                Color::CYAN.with_alpha(128)
+            }
+        })
+        .collect();
+
+    let stretched = NineSlice::new(data.clone(), 8, 8, Insets::uniform(2))
+        .unwrap()
+        .with_rect(vec2(2, 2), 36, 20);
+    let tiled = NineSlice::new(data, 8, 8, Insets::uniform(2))
+        .unwrap()
+        .with_center(SliceMode::Tile)
+        .with_edges(SliceMode::Tile)
+        .with_rect(vec2(20, 26), 42, 20);
+
+    let mut canvas = render(&stretched);
+    canvas.drawable(&tiled);
+    assert_golden("nine_slice", &canvas);
+}
+
+#[test]
+fn lighting() {
+    #[rustfmt::skip]
+    let walls = [
+        false, false, false, false, false, false, false, false,
+        false, false, true,  false, false, false, false, false,
+        false, false, true,  false, false, true,  true,  false,
+        false, false, false, false, false, false, false, false,
+        false, false, false, false, false, false, false, false,
+        false, false, false, false, false, false, false, false,
+    ];
+    let lighting = Lighting::new(Color::from_rgb(24, 24, 40))
+        .with_tiles(&walls, 8, 8.0, 8.0)
+        .with_occluder(Occluder::polygon(vec![
+            vec2(12.0, 34.0),
+            vec2(18.0, 40.0),
+            vec2(12.0, 46.0),
+        ]))
+        .with_light(Light::new(vec2(8.0, 16.0), 40.0, Color::ORANGE))
+        .with_light(Light::cone(vec2(60.0, 44.0), 48.0, Color::CYAN, 3.6, 0.8).with_falloff(1.0))
+        .with_light(Light::new(vec2(40.0, 8.0), 12.0, Color::WHITE).with_shadows(false));
+
+    let mut canvas = render(Sketch::new().rect(vec2(0, 0), 64, 48, Color::WHITE));
+    canvas.drawable(&lighting);
+    assert_golden("lighting", &canvas);
+}
+
+#[test]
+fn bitmap_font() {
+    // 3x5 glyphs in a grid of 4x6 cells
+    #[rustfmt::skip]
+    let glyphs = [
+        "###.#.#.#....#..",
+        "#...#.#.#....#..",
+        "##..###.#....#..",
+        "#...#.#.#.......",
+        "###.#.#.###..#..",
+        "................",
+    ];
+    let data: Vec<Color> = glyphs
+        .concat()
+        .chars()
+        .map(|c| {
+            if c == '#' {
+                Color::WHITE
+            } else {
+                Color::TRANSPARENT
+            }
+        })
+        .collect();
+    let font = BitmapFont::from_grid(data, 16, 6, 4, 6, "EHL!")
+        .unwrap()
+        .with_kerning('L', 'L', -1);
+
+    let mut canvas = render(&BitmapText::new(vec2(2, 2), &font, "HELL!\nHE"));
+    canvas.drawable(
+        &BitmapText::new(vec2(2, 20), &font, "HELL!")
+            .with_tint(Color::ORANGE)
+            .with_scale(3),
+    );
+
+    assert_golden("bitmap_font", &canvas);
+}
+
+#[cfg(feature = "text")]
+#[test]
+fn text() {
+    use qilin::text::TextSketch;
+
+    let font = include_bytes!("../examples/assets/Roboto-Medium.ttf");
+    let mut canvas = render(&Sketch::new());
+    canvas.drawable(
+        &TextSketch::new(vec2(2, 2), font)
+            .with_color(Color::WHITE)
+            .with_text("Qilin", 20.0),
+    );
+    canvas.drawable(
+        &TextSketch::new(vec2(2, 26), font)
+            .with_color(Color::YELLOW)
+            .with_text("ssa", 16.0)
+            .with_ssa(true),
+    );
+
+    assert_golden("text", &canvas);
+}
+
+#[cfg(feature = "text")]
+#[test]
+fn text_layout() {
+    use qilin::text::{Font, HorizontalAlign, TextSketch, VerticalAlign};
+
+    let font = Font::from_bytes(include_bytes!("../examples/assets/Roboto-Medium.ttf")).unwrap();
+    let mut canvas = render(Sketch::new().rect(vec2(2, 2), 60, 44, Color::from_rgb(0, 0, 96)));
+    canvas.drawable(
+        &TextSketch::with_font(vec2(2, 2), &font)
+            .with_color(Color::WHITE)
+            .with_ssa(false)
+            .with_text("wrapped and centered", 9.0)
+            .with_bounds(60.0, 44.0)
+            .with_align(HorizontalAlign::Center, VerticalAlign::Middle)
+            .with_line_spacing(1.2),
+    );
+    canvas.drawable(
+        &TextSketch::with_font(vec2(2, 2), &font)
+            .with_color(Color::YELLOW)
+            .with_ssa(false)
+            .with_text("42", 10.0)
+            .with_bounds(60.0, 44.0)
+            .with_align(HorizontalAlign::Right, VerticalAlign::Bottom),
+    );
+
+    assert_golden("text_layout", &canvas);
+}
+
+#[cfg(feature = "text")]
+#[test]
+fn rich_text() {
+    use qilin::text::{Font, TextSketch, TextSpan};
+
+    let font = Font::from_bytes(include_bytes!("../examples/assets/Roboto-Medium.ttf")).unwrap();
+    let mut canvas = render(&Sketch::new());
+    canvas.drawable(
+        &TextSketch::with_font(vec2(2, 2), &font)
+            .with_color(Color::WHITE)
+            .with_ssa(false)
+            .with_markup("Run, [color=red][size=14]DANGER[/size][/color]!", 10.0)
+            .with_max_width(60.0),
+    );
+    canvas.drawable(
+        &TextSketch::with_font(vec2(2, 34), &font)
+            .with_bold_font(&font)
+            .with_color(Color::GRAY)
+            .with_ssa(false)
+            .with_span(TextSpan::new("gold ", 12.0).with_color(Color::YELLOW))
+            .with_span(TextSpan::new("x3", 9.0).with_font(1)),
+    );
+
+    assert_golden("rich_text", &canvas);
+}
+
+#[cfg(feature = "text")]
+#[test]
+fn text_effects() {
+    use qilin::text::effects::TextAnimation;
+    use qilin::text::{Font, TextSketch};
+    use std::time::Duration;
+
+    let font = Font::from_bytes(include_bytes!("../examples/assets/Roboto-Medium.ttf")).unwrap();
+    let mut canvas = render(Sketch::new().rect(vec2(0, 0), 64, 48, Color::from_rgb(40, 120, 40)));
+    canvas.drawable(
+        &TextSketch::with_font(vec2(4, 2), &font)
+            .with_color(Color::WHITE)
+            .with_ssa(false)
+            .with_text("HUD", 16.0)
+            .with_outline(Color::BLACK, 1)
+            .with_shadow(Color::BLACK.with_alpha(128), vec2(2, 2)),
+    );
+    canvas.drawable(
+        &TextSketch::with_font(vec2(4, 26), &font)
+            .with_color(Color::YELLOW)
+            .with_ssa(false)
+            .with_text("wave and type", 10.0)
+            .with_animation(TextAnimation::Wave {
+                amplitude: 2.0,
+                speed: 1.0,
+                length: 6.0,
+            })
+            .with_animation(TextAnimation::Typewriter { speed: 20.0 })
+            .with_time(Duration::from_millis(400)),
+    );
+
+    assert_golden("text_effects", &canvas);
+}
+
+/// Draw text in both blending modes, with and without gamma, over a light and a dark background.
+#[cfg(feature = "text")]
+fn text_blending(subpixel: bool) -> Canvas {
+    use qilin::text::{Font, TextSketch};
+
+    let font = Font::from_bytes(include_bytes!("../examples/assets/Roboto-Medium.ttf")).unwrap();
+    let mut canvas = render(Sketch::new().rect(vec2(0, 0), 64, 24, Color::WHITE).rect(
+        vec2(0, 24),
+        64,
+        24,
+        Color::from_rgb(0, 0, 96),
+    ));
+
+    for (y, color) in [(0, Color::BLUE), (24, Color::YELLOW)] {
+        for (x, gamma) in [(2, 1.0), (32, 2.2)] {
+            canvas.drawable(
+                &TextSketch::with_font(vec2(x, y), &font)
+                    .with_color(color)
+                    .with_ssa(subpixel)
+                    .with_gamma(gamma)
+                    .with_text("Aw", 16.0),
+            );
+        }
+    }
+
+    canvas
+}
+
+#[cfg(feature = "text")]
+#[test]
+fn text_grayscale() { assert_golden("text_grayscale", &text_blending(false)); }
+
+#[cfg(feature = "text")]
+#[test]
+fn text_subpixel() { assert_golden("text_subpixel", &text_blending(true)); }